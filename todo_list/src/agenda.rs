@@ -0,0 +1,91 @@
+use crate::todo::{Status, Todo, TodoList};
+use chrono::{Days, NaiveDate};
+
+/// Open todos with a due date, grouped relative to a given day. Each group is sorted by due
+/// date, then by priority (highest first), then by id.
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct Agenda<'a> {
+    pub overdue: Vec<&'a Todo>,
+    pub today: Vec<&'a Todo>,
+    /// Due within the six days after today.
+    pub this_week: Vec<&'a Todo>,
+}
+
+impl TodoList {
+    pub fn agenda(&self, today: NaiveDate) -> Agenda<'_> {
+        let week_end = today
+            .checked_add_days(Days::new(6))
+            .unwrap_or(NaiveDate::MAX);
+        let mut todos = self
+            .list()
+            .iter()
+            .filter(|x| x.status != Status::Done)
+            .filter_map(|x| x.due.map(|due| (due, x)))
+            .collect::<Vec<_>>();
+        todos.sort_by(|(a_due, a), (b_due, b)| {
+            a_due
+                .cmp(b_due)
+                .then(b.priority.cmp(&a.priority))
+                .then(a.id.cmp(&b.id))
+        });
+
+        let mut agenda = Agenda::default();
+        for (due, todo) in todos {
+            if due < today {
+                agenda.overdue.push(todo);
+            } else if due == today {
+                agenda.today.push(todo);
+            } else if due <= week_end {
+                agenda.this_week.push(todo);
+            }
+        }
+
+        agenda
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::Priority;
+
+    fn date(day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(2026, 10, day)
+    }
+
+    fn ids(todos: &[&Todo]) -> Vec<usize> {
+        todos.iter().map(|x| x.id).collect()
+    }
+
+    #[test]
+    fn should_group_open_todos_by_due_date() {
+        let todo_list = TodoList::new(
+            [
+                (date(14), Status::ToDo, Priority::Normal),
+                (date(16), Status::InProgress, Priority::Low),
+                (date(15), Status::Done, Priority::Normal),
+                (date(22), Status::ToDo, Priority::Normal),
+                (date(23), Status::ToDo, Priority::Normal),
+                (None, Status::ToDo, Priority::Urgent),
+                (date(16), Status::ToDo, Priority::High),
+                (date(10), Status::ToDo, Priority::Normal),
+            ]
+            .into_iter()
+            .enumerate()
+            .map(|(id, (due, status, priority))| Todo {
+                id,
+                title: format!("title{}", id),
+                status,
+                due,
+                priority,
+                ..Default::default()
+            }),
+        );
+
+        let agenda = todo_list.agenda(date(16).unwrap());
+
+        assert_eq!(ids(&agenda.overdue), vec![7, 0]);
+        assert_eq!(ids(&agenda.today), vec![6, 1]);
+        assert_eq!(ids(&agenda.this_week), vec![3]);
+    }
+}
@@ -0,0 +1,428 @@
+use crate::table::wrap;
+use crate::Error;
+use std::iter::Peekable;
+use std::str::FromStr;
+
+const HELP_WIDTH: usize = 80;
+/// Longer options in `--help` put their description on the next line.
+const MAX_OPTION_WIDTH: usize = 24;
+
+/// One option a subcommand accepts: a flag, or an option taking a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opt {
+    short: Option<char>,
+    long: &'static str,
+    value: Option<&'static str>,
+    required: bool,
+    repeated: bool,
+    help: &'static str,
+}
+
+impl Opt {
+    pub const fn flag(short: Option<char>, long: &'static str, help: &'static str) -> Self {
+        Opt {
+            short,
+            long,
+            value: None,
+            required: false,
+            repeated: false,
+            help,
+        }
+    }
+
+    /// An option taking a value, shown as `value` in usage text.
+    pub const fn value(
+        short: Option<char>,
+        long: &'static str,
+        value: &'static str,
+        help: &'static str,
+    ) -> Self {
+        Opt {
+            value: Some(value),
+            ..Self::flag(short, long, help)
+        }
+    }
+
+    pub const fn required(self) -> Self {
+        Opt {
+            required: true,
+            ..self
+        }
+    }
+
+    /// Allows the option more than once; every value is kept.
+    pub const fn repeated(self) -> Self {
+        Opt {
+            repeated: true,
+            ..self
+        }
+    }
+
+    /// The name used in usage text and in errors about an option that was not given.
+    fn name(&self) -> String {
+        match self.short {
+            Some(x) => format!("-{}", x),
+            None => format!("--{}", self.long),
+        }
+    }
+
+    fn synopsis(&self) -> String {
+        let mut s = self.name();
+        if let Some(value) = self.value {
+            s = format!("{} {}", s, value);
+        }
+        if !self.required {
+            s = format!("[{}]", s);
+        }
+        if self.repeated {
+            s.push_str("...");
+        }
+        s
+    }
+}
+
+/// What a subcommand accepts, from which both its parser and its `--help` text are built.
+///
+/// Options may be given as `-t value`, `-tvalue`, `--title value` or `--title=value`, short
+/// flags may be combined as `-rw`, and everything after `--` is positional. An argument that
+/// starts with `-` is never taken as an option's value, so such values need the `=` form.
+#[derive(Debug)]
+pub struct Spec {
+    pub about: &'static str,
+    /// Usage text for the positional arguments, which the subcommand checks itself.
+    /// Positional arguments are rejected if this is empty.
+    pub positionals: &'static str,
+    pub options: &'static [Opt],
+}
+
+/// The options and positional arguments given to a subcommand.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Matches {
+    /// `(long name, name as given, value)` in the order given.
+    options: Vec<(&'static str, String, Option<String>)>,
+    positionals: Vec<String>,
+}
+
+impl Matches {
+    pub fn flag(&self, long: &str) -> bool {
+        self.options.iter().any(|(x, _, _)| *x == long)
+    }
+
+    pub fn value(&self, long: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(x, _, _)| *x == long)
+            .and_then(|(_, _, value)| value.as_deref())
+    }
+
+    pub fn values<'a>(&'a self, long: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.options
+            .iter()
+            .filter(move |(x, _, _)| *x == long)
+            .filter_map(|(_, _, value)| value.as_deref())
+    }
+
+    /// Parses the value of `long` with `parse`, naming the option as given if it fails.
+    pub fn parse_with<T>(
+        &self,
+        long: &str,
+        parse: impl Fn(&str) -> Result<T, String>,
+    ) -> Result<Option<T>, Error> {
+        self.options
+            .iter()
+            .find(|(x, _, _)| *x == long)
+            .and_then(|(_, name, value)| Some((name, value.as_deref()?)))
+            .map(|(name, value)| parse(value).map_err(|x| Error::invalid_argument(name, x)))
+            .transpose()
+    }
+
+    pub fn parse<T: FromStr<Err = String>>(&self, long: &str) -> Result<Option<T>, Error> {
+        self.parse_with(long, str::parse)
+    }
+
+    pub fn positionals(&self) -> &[String] {
+        &self.positionals
+    }
+
+    fn push(&mut self, opt: &Opt, name: String, value: Option<String>) -> Result<(), Error> {
+        if !opt.repeated && self.flag(opt.long) {
+            return Err(Error::invalid_argument(
+                &name,
+                "option given more than once",
+            ));
+        }
+        self.options.push((opt.long, name, value));
+        Ok(())
+    }
+}
+
+/// Parses a numeric todo id, e.g. for [`Matches::parse_with`].
+pub fn parse_id(s: &str) -> Result<usize, String> {
+    s.parse().map_err(|_| format!("invalid id; {}", s))
+}
+
+/// Whether `--help` or `-h` was given before any `--`.
+pub fn wants_help(args: &[String]) -> bool {
+    args.iter()
+        .take_while(|x| *x != "--")
+        .any(|x| x == "-h" || x == "--help")
+}
+
+fn is_option(arg: &str) -> bool {
+    arg.starts_with('-') && arg != "-"
+}
+
+impl Spec {
+    pub fn parse(&self, args: impl IntoIterator<Item = String>) -> Result<Matches, Error> {
+        let mut args = args.into_iter().peekable();
+        let mut matches = Matches::default();
+
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                matches.positionals.extend(args);
+                break;
+            } else if !is_option(&arg) && self.positionals.is_empty() {
+                return Err(Error::usage(format!("unexpected argument; {}", arg)));
+            } else if !is_option(&arg) {
+                matches.positionals.push(arg);
+            } else if let Some(long) = arg.strip_prefix("--") {
+                let (long, inline) = match long.split_once('=') {
+                    Some((long, value)) => (long, Some(value.to_string())),
+                    None => (long, None),
+                };
+                let name = format!("--{}", long);
+                let opt = self.find(|x| x.long == long, &name)?;
+                let value = match (opt.value, inline) {
+                    (None, None) => None,
+                    (None, Some(_)) => {
+                        return Err(Error::invalid_argument(&name, "option takes no value"))
+                    }
+                    (Some(_), Some(x)) => Some(x),
+                    (Some(_), None) => Some(Self::next_value(&mut args, &name)?),
+                };
+                matches.push(opt, name, value)?;
+            } else {
+                for (i, c) in arg.char_indices().skip(1) {
+                    let name = format!("-{}", c);
+                    let opt = self.find(|x| x.short == Some(c), &name)?;
+                    if opt.value.is_none() {
+                        matches.push(opt, name, None)?;
+                        continue;
+                    }
+
+                    let value = match &arg[i + c.len_utf8()..] {
+                        "" => Self::next_value(&mut args, &name)?,
+                        x => x.to_string(),
+                    };
+                    matches.push(opt, name, Some(value))?;
+                    break;
+                }
+            }
+        }
+
+        match self
+            .options
+            .iter()
+            .find(|x| x.required && !matches.flag(x.long))
+        {
+            Some(x) => Err(Error::invalid_argument(&x.name(), "option required")),
+            None => Ok(matches),
+        }
+    }
+
+    fn find(&self, predicate: impl Fn(&Opt) -> bool, name: &str) -> Result<&Opt, Error> {
+        self.options
+            .iter()
+            .find(|x| predicate(x))
+            .ok_or_else(|| Error::usage(format!("invalid option; {}", name)))
+    }
+
+    fn next_value(
+        args: &mut Peekable<impl Iterator<Item = String>>,
+        name: &str,
+    ) -> Result<String, Error> {
+        args.next_if(|x| !is_option(x))
+            .ok_or_else(|| Error::invalid_argument(name, "option requires a value"))
+    }
+
+    /// The synopsis of the subcommand invoked as `name`, wrapped to fit the help text.
+    pub fn usage(&self, name: &str) -> String {
+        let prefix = format!("usage: todo_list {}", name);
+        let indent = " ".repeat(prefix.len());
+        let mut synopsis = vec![prefix];
+        let pieces = self
+            .options
+            .iter()
+            .map(Opt::synopsis)
+            .chain((!self.positionals.is_empty()).then(|| self.positionals.to_string()));
+        for piece in pieces {
+            let line = synopsis.last().unwrap();
+            if line.len() + 1 + piece.len() > HELP_WIDTH && line.len() > indent.len() {
+                synopsis.push(indent.clone());
+            }
+            let line = synopsis.last_mut().unwrap();
+            line.push(' ');
+            line.push_str(&piece);
+        }
+
+        synopsis.join("\n")
+    }
+
+    /// The `--help` text for the subcommand invoked as `name`.
+    pub fn help(&self, name: &str) -> String {
+        let mut rows = self
+            .options
+            .iter()
+            .map(|x| {
+                let short = x.short.map(|x| format!("-{}, ", x)).unwrap_or_default();
+                let value = x.value.map(|x| format!(" {}", x)).unwrap_or_default();
+                (format!("{}--{}{}", short, x.long, value), x.help)
+            })
+            .collect::<Vec<_>>();
+        rows.push((String::from("-h, --help"), "print this help"));
+        let width = rows
+            .iter()
+            .map(|(x, _)| x.len())
+            .filter(|&x| x <= MAX_OPTION_WIDTH)
+            .max()
+            .unwrap_or(0);
+
+        let mut help = format!(
+            "{}\n\n{}\n\noptions:",
+            self.usage(name),
+            wrap(self.about, HELP_WIDTH).join("\n")
+        );
+        for (option, text) in rows {
+            help = if option.len() > width {
+                // Too long to line up with the others, so the text goes on the next line.
+                format!("{}\n    {}\n    {:width$}  {}", help, option, "", text)
+            } else {
+                format!("{}\n    {:width$}  {}", help, option, text)
+            };
+        }
+        help
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::split_into_string_iter;
+    use rstest::*;
+
+    const SPEC: Spec = Spec {
+        about: "Does things.",
+        positionals: "<id>...",
+        options: &[
+            Opt::value(Some('t'), "title", "<title>", "title of the todo").required(),
+            Opt::value(Some('l'), "label", "<label>", "label to add").repeated(),
+            Opt::value(None, "due", "<date>", "due date"),
+            Opt::flag(Some('r'), "reverse", "reverse the order"),
+            Opt::flag(Some('w'), "wrap", "wrap long values"),
+        ],
+    };
+
+    #[rstest]
+    #[case("-t a 1", Some("a"), vec![], vec!["1"])]
+    #[case("--title=a", Some("a"), vec![], vec![])]
+    #[case("-ta -l x --label=y -l z", Some("a"), vec!["x", "y", "z"], vec![])]
+    #[case("1 --title a 2", Some("a"), vec![], vec!["1", "2"])]
+    #[case("-t a -- -l -", Some("a"), vec![], vec!["-l", "-"])]
+    #[case("-t=a", Some("=a"), vec![], vec![])]
+    fn should_parse_values(
+        #[case] args: &'static str,
+        #[case] title: Option<&str>,
+        #[case] labels: Vec<&str>,
+        #[case] positionals: Vec<&str>,
+    ) {
+        let matches = SPEC.parse(split_into_string_iter(args)).unwrap();
+
+        assert_eq!(matches.value("title"), title);
+        assert_eq!(matches.values("label").collect::<Vec<_>>(), labels);
+        assert_eq!(matches.positionals(), positionals);
+    }
+
+    #[rstest]
+    #[case("-t a -rw", true, true)]
+    #[case("-wt a", false, true)]
+    #[case("-t a --reverse", true, false)]
+    fn should_parse_combined_flags(
+        #[case] args: &'static str,
+        #[case] reverse: bool,
+        #[case] wrap: bool,
+    ) {
+        let matches = SPEC.parse(split_into_string_iter(args)).unwrap();
+
+        assert_eq!(matches.value("title"), Some("a"));
+        assert_eq!(
+            (matches.flag("reverse"), matches.flag("wrap")),
+            (reverse, wrap)
+        );
+    }
+
+    #[rstest]
+    #[case("-l x", "-t: option required")]
+    #[case("-t", "-t: option requires a value")]
+    #[case("-t -l x", "-t: option requires a value")]
+    #[case("-t a --due", "--due: option requires a value")]
+    #[case("-t a -x", "invalid option; -x")]
+    #[case("-t a -rx", "invalid option; -x")]
+    #[case("-t a --size 1", "invalid option; --size")]
+    #[case("-t a --wrap=yes", "--wrap: option takes no value")]
+    #[case("-t a --title b", "--title: option given more than once")]
+    fn should_return_error_if_invalid_args_given(
+        #[case] args: &'static str,
+        #[case] expected: &str,
+    ) {
+        let res = SPEC.parse(split_into_string_iter(args));
+
+        assert!(res.is_err(), "not err; res={:?}", res);
+        assert_eq!(res.unwrap_err().to_string(), expected);
+    }
+
+    #[test]
+    fn should_name_option_as_given_when_value_is_invalid() {
+        let matches = SPEC
+            .parse(split_into_string_iter("-t a --due=soon"))
+            .unwrap();
+
+        let res = matches.parse_with("due", |x| Err::<(), _>(format!("invalid date; {}", x)));
+
+        assert_eq!(res.unwrap_err().to_string(), "--due: invalid date; soon");
+        assert!(matches!(
+            matches.parse_with("label", |x| Ok(x.len())),
+            Ok(None)
+        ));
+    }
+
+    #[rstest]
+    #[case("--help", true)]
+    #[case("-t a -h", true)]
+    #[case("-- --help", false)]
+    #[case("-t a", false)]
+    fn should_detect_help(#[case] args: &'static str, #[case] expected: bool) {
+        assert_eq!(
+            wants_help(&split_into_string_iter(args).collect::<Vec<_>>()),
+            expected
+        );
+    }
+
+    #[test]
+    fn should_generate_help() {
+        assert_eq!(
+            SPEC.help("do"),
+            "\
+usage: todo_list do -t <title> [-l <label>]... [--due <date>] [-r] [-w] <id>...
+
+Does things.
+
+options:
+    -t, --title <title>  title of the todo
+    -l, --label <label>  label to add
+    --due <date>         due date
+    -r, --reverse        reverse the order
+    -w, --wrap           wrap long values
+    -h, --help           print this help"
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use crate::date::{format_date, DATE_FORMAT};
+use crate::store::StoreKind;
+use crate::table::{parse_columns, Column};
+use crate::Error;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Key {
+    File,
+    Store,
+    DefaultLabel,
+    DateFormat,
+    Columns,
+}
+
+impl Key {
+    pub const ALL: [Key; 5] = [
+        Self::File,
+        Self::Store,
+        Self::DefaultLabel,
+        Self::DateFormat,
+        Self::Columns,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Store => "store",
+            Self::DefaultLabel => "default_label",
+            Self::DateFormat => "date_format",
+            Self::Columns => "columns",
+        }
+    }
+
+    /// The environment variable overriding the key, e.g. `TODO_LIST_DATE_FORMAT`.
+    pub fn env_var(&self) -> String {
+        format!("TODO_LIST_{}", self.name().to_uppercase())
+    }
+
+    fn validate(&self, value: &str) -> Result<(), String> {
+        match self {
+            Self::File | Self::DefaultLabel => Ok(()),
+            Self::Store => value.parse::<StoreKind>().map(|_| ()),
+            Self::DateFormat if format_date(NaiveDate::default(), value).is_none() => {
+                Err(format!("invalid date format; {}", value))
+            }
+            Self::DateFormat => Ok(()),
+            Self::Columns => parse_columns(value).map(|_| ()),
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|x| x.name() == s)
+            .ok_or_else(|| format!("invalid config key; {}", s))
+    }
+}
+
+/// Where a setting's value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File,
+    Env,
+    Flag,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Default => "default",
+                Self::File => "config",
+                Self::Env => "env",
+                Self::Flag => "flag",
+            }
+        )
+    }
+}
+
+/// Settings resolved from, highest precedence first: the `--file`/`--store` flags,
+/// `TODO_LIST_<KEY>` environment variables, the config file, then built-in defaults.
+/// Every value is validated when it enters a layer, so the typed getters cannot fail.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    path: Option<PathBuf>,
+    file: BTreeMap<Key, String>,
+    env: BTreeMap<Key, String>,
+    flags: BTreeMap<Key, String>,
+}
+
+impl Config {
+    /// `$TODO_LIST_CONFIG`, else `$XDG_CONFIG_HOME/todo_list/config.toml`,
+    /// else `$HOME/.config/todo_list/config.toml`.
+    pub fn path_from(env: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+        let non_empty = |name: &str| env(name).filter(|x| !x.is_empty()).map(PathBuf::from);
+
+        non_empty("TODO_LIST_CONFIG").or_else(|| {
+            non_empty("XDG_CONFIG_HOME")
+                .or_else(|| non_empty("HOME").map(|x| x.join(".config")))
+                .map(|x| x.join("todo_list").join("config.toml"))
+        })
+    }
+
+    pub fn load() -> Result<Self, Error> {
+        Self::load_from(|x| env::var(x).ok())
+    }
+
+    /// Reads the config file, if any, and the overrides in `env`.
+    pub fn load_from(env: impl Fn(&str) -> Option<String>) -> Result<Self, Error> {
+        let mut config = Config {
+            path: Self::path_from(&env),
+            ..Default::default()
+        };
+
+        if let Some(path) = &config.path {
+            let invalid = |x: String| Error::Invalid(format!("{}: {}", path.display(), x));
+            let text = match fs::read_to_string(path) {
+                Ok(x) => x,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+                Err(err) => return Err(Error::io(path)(err)),
+            };
+            let table = toml::from_str::<BTreeMap<String, String>>(&text)
+                .map_err(|x| invalid(x.message().to_string()))?;
+            for (name, value) in table {
+                let key = name.parse::<Key>().map_err(invalid)?;
+                key.validate(&value)
+                    .map_err(|x| invalid(format!("{}: {}", key, x)))?;
+                config.file.insert(key, value);
+            }
+        }
+
+        for key in Key::ALL {
+            let var = key.env_var();
+            if let Some(value) = env(&var) {
+                key.validate(&value)
+                    .map_err(|x| Error::Invalid(format!("{}: {}", var, x)))?;
+                config.env.insert(key, value);
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Overrides `key` for this run only, as a command-line flag does.
+    pub fn set_flag(&mut self, key: Key, value: String) {
+        self.flags.insert(key, value);
+    }
+
+    /// The value of `key` and where it came from; `None` if nothing sets it and it
+    /// has no default.
+    pub fn get(&self, key: Key) -> Option<(String, Source)> {
+        [
+            (&self.flags, Source::Flag),
+            (&self.env, Source::Env),
+            (&self.file, Source::File),
+        ]
+        .into_iter()
+        .find_map(|(layer, source)| layer.get(&key).map(|x| (x.clone(), source)))
+        .or_else(|| self.default_value(key).map(|x| (x, Source::Default)))
+    }
+
+    fn default_value(&self, key: Key) -> Option<String> {
+        match key {
+            Key::File => Some(self.store().default_path().to_string()),
+            Key::Store => Some(StoreKind::default().to_string()),
+            Key::DefaultLabel => None,
+            Key::DateFormat => Some(DATE_FORMAT.to_string()),
+            Key::Columns => Some(
+                Column::DEFAULT
+                    .iter()
+                    .map(|x| x.header())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+        }
+    }
+
+    fn value(&self, key: Key) -> Option<String> {
+        self.get(key).map(|(value, _)| value)
+    }
+
+    pub fn store(&self) -> StoreKind {
+        self.value(Key::Store)
+            .and_then(|x| x.parse().ok())
+            .unwrap_or_default()
+    }
+
+    pub fn file(&self) -> String {
+        self.value(Key::File).unwrap_or_default()
+    }
+
+    pub fn default_label(&self) -> Option<String> {
+        self.value(Key::DefaultLabel)
+    }
+
+    pub fn date_format(&self) -> String {
+        self.value(Key::DateFormat).unwrap_or_default()
+    }
+
+    pub fn columns(&self) -> Vec<Column> {
+        self.value(Key::Columns)
+            .and_then(|x| parse_columns(&x).ok())
+            .unwrap_or_else(|| Column::DEFAULT.to_vec())
+    }
+
+    /// Sets `key` in the config file, or removes it if `value` is empty.
+    /// Call `save` to write the change.
+    pub fn set(&mut self, key: Key, value: &str) -> Result<(), Error> {
+        if value.is_empty() {
+            self.file.remove(&key);
+            return Ok(());
+        }
+        key.validate(value)
+            .map_err(|x| Error::invalid_argument(key.name(), x))?;
+        self.file.insert(key, value.to_string());
+
+        Ok(())
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let path = self.path.as_ref().ok_or_else(|| {
+            Error::Invalid(String::from(
+                "no config file location; set TODO_LIST_CONFIG or HOME",
+            ))
+        })?;
+        let table = self
+            .file
+            .iter()
+            .map(|(key, value)| (key.name(), value))
+            .collect::<BTreeMap<_, _>>();
+        let text = toml::to_string(&table)
+            .map_err(|x| Error::Invalid(format!("{}: {}", path.display(), x)))?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(Error::io(dir))?;
+        }
+        fs::write(path, text).map_err(Error::io(path))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use std::collections::HashMap;
+    use tempfile::tempdir;
+
+    fn env_from(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<HashMap<_, _>>();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[rstest]
+    #[case(&[("TODO_LIST_CONFIG", "/a/config.toml"), ("HOME", "/home/u")], Some("/a/config.toml"))]
+    #[case(&[("XDG_CONFIG_HOME", "/xdg"), ("HOME", "/home/u")], Some("/xdg/todo_list/config.toml"))]
+    #[case(&[("XDG_CONFIG_HOME", ""), ("HOME", "/home/u")], Some("/home/u/.config/todo_list/config.toml"))]
+    #[case(&[], None)]
+    fn should_locate_config_file(#[case] vars: &[(&str, &str)], #[case] expected: Option<&str>) {
+        assert_eq!(
+            Config::path_from(env_from(vars)),
+            expected.map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn should_resolve_flags_over_env_over_file_over_defaults() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            "file = \"from-file.json\"\nstore = \"sqlite\"\ndate_format = \"%d/%m/%Y\"\n",
+        )
+        .unwrap();
+
+        let mut config = Config::load_from(env_from(&[
+            ("TODO_LIST_CONFIG", path.to_str().unwrap()),
+            ("TODO_LIST_FILE", "from-env.json"),
+            ("TODO_LIST_STORE", "json"),
+        ]))
+        .unwrap();
+        config.set_flag(Key::File, String::from("from-flag.json"));
+
+        assert_eq!(
+            config.get(Key::File),
+            Some((String::from("from-flag.json"), Source::Flag))
+        );
+        assert_eq!(
+            config.get(Key::Store),
+            Some((String::from("json"), Source::Env))
+        );
+        assert_eq!(
+            config.get(Key::DateFormat),
+            Some((String::from("%d/%m/%Y"), Source::File))
+        );
+        assert_eq!(config.columns(), Column::DEFAULT.to_vec());
+        assert_eq!(config.default_label(), None);
+    }
+
+    #[test]
+    fn should_default_file_to_store_path() {
+        let mut config = Config::default();
+        assert_eq!(config.file(), "todo-list.json");
+
+        config.set_flag(Key::Store, String::from("sqlite"));
+        assert_eq!(config.file(), "todo-list.db");
+    }
+
+    #[rstest]
+    #[case("store = \"csv\"", r"store: invalid store; csv")]
+    #[case("colour = \"red\"", r"invalid config key; colour")]
+    #[case("columns = \"id,size\"", r"columns: invalid column; size")]
+    #[case("store = 1", r"config.toml: ")]
+    fn should_return_error_if_config_file_invalid(#[case] text: &str, #[case] pattern: &str) {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, text).unwrap();
+        let re = regex::Regex::new(pattern).unwrap();
+
+        let res = Config::load_from(env_from(&[("TODO_LIST_CONFIG", path.to_str().unwrap())]));
+
+        assert!(res.is_err(), "not err; res={:?}", res);
+        let message = &res.unwrap_err().to_string();
+        assert!(re.is_match(message), "message={message}");
+    }
+
+    #[rstest]
+    #[case("%Q")]
+    #[case("%H:%M")]
+    fn should_return_error_if_env_invalid(#[case] date_format: &str) {
+        let res = Config::load_from(env_from(&[("TODO_LIST_DATE_FORMAT", date_format)]));
+
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            format!(
+                "TODO_LIST_DATE_FORMAT: invalid date format; {}",
+                date_format
+            )
+        );
+    }
+
+    #[test]
+    fn should_save_and_reload_set_values() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("todo_list").join("config.toml");
+        let env = env_from(&[("TODO_LIST_CONFIG", path.to_str().unwrap())]);
+
+        let mut config = Config::load_from(&env).unwrap();
+        config.set(Key::DefaultLabel, "inbox").unwrap();
+        config.set(Key::Columns, "id,title").unwrap();
+        assert!(config.set(Key::Store, "csv").is_err());
+        config.save().unwrap();
+
+        let mut config = Config::load_from(&env).unwrap();
+        assert_eq!(config.default_label(), Some(String::from("inbox")));
+        assert_eq!(config.columns(), vec![Column::Id, Column::Title]);
+
+        config.set(Key::DefaultLabel, "").unwrap();
+        config.save().unwrap();
+        assert_eq!(Config::load_from(&env).unwrap().default_label(), None);
+    }
+}
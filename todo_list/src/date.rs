@@ -0,0 +1,130 @@
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate, Utc, Weekday};
+use std::fmt::Write;
+
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// `date` in the strftime pattern `format`, or `None` if the pattern cannot format a date,
+/// e.g. because it is malformed or asks for a time of day.
+pub fn format_date(date: NaiveDate, format: &str) -> Option<String> {
+    let mut s = String::new();
+    write!(s, "{}", date.format(format)).ok()?;
+    Some(s)
+}
+
+/// The calendar day `t` falls on in the local time zone.
+pub fn local_date(t: DateTime<Utc>) -> NaiveDate {
+    t.with_timezone(&Local).date_naive()
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    let weekday = match s {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thur" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(weekday)
+}
+
+/// Parses `YYYY-MM-DD` or a date relative to `today`: `today`, `tomorrow`, `yesterday`,
+/// `+3d`/`+2w`/`+1m` (days, weeks, months ahead; `-` goes back) and weekday names such as
+/// `fri` or `next fri`, both meaning the first such day after `today`.
+pub fn parse_date(s: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let invalid = || format!("invalid date; {}", s);
+    let lower = s.trim().to_lowercase();
+
+    if let Ok(date) = NaiveDate::parse_from_str(&lower, DATE_FORMAT) {
+        return Ok(date);
+    }
+
+    match &lower[..] {
+        "today" => return Ok(today),
+        "tomorrow" => return today.checked_add_days(Days::new(1)).ok_or_else(invalid),
+        "yesterday" => return today.checked_sub_days(Days::new(1)).ok_or_else(invalid),
+        _ => (),
+    }
+
+    if let Some(sign) = lower.chars().next().filter(|x| *x == '+' || *x == '-') {
+        let rest = &lower[1..];
+        let (n, unit) = rest.split_at(rest.char_indices().last().map_or(0, |(i, _)| i));
+        let n = n.parse::<u32>().map_err(|_| invalid())?;
+        let date = match (unit, sign) {
+            ("d", '+') => today.checked_add_days(Days::new(n.into())),
+            ("d", _) => today.checked_sub_days(Days::new(n.into())),
+            ("w", '+') => today.checked_add_days(Days::new(7 * u64::from(n))),
+            ("w", _) => today.checked_sub_days(Days::new(7 * u64::from(n))),
+            ("m", '+') => today.checked_add_months(Months::new(n)),
+            ("m", _) => today.checked_sub_months(Months::new(n)),
+            _ => None,
+        };
+        return date.ok_or_else(invalid);
+    }
+
+    let weekday = lower.strip_prefix("next ").unwrap_or(&lower);
+    match parse_weekday(weekday) {
+        Some(weekday) => {
+            let days_ahead =
+                (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday() - 1)
+                    % 7
+                    + 1;
+            today
+                .checked_add_days(Days::new(days_ahead.into()))
+                .ok_or_else(invalid)
+        }
+        None => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, DATE_FORMAT).unwrap()
+    }
+
+    // 2026-10-16 is a Friday.
+    #[rstest]
+    #[case("2026-11-01", "2026-11-01")]
+    #[case("today", "2026-10-16")]
+    #[case("Tomorrow", "2026-10-17")]
+    #[case("yesterday", "2026-10-15")]
+    #[case("+3d", "2026-10-19")]
+    #[case("-3d", "2026-10-13")]
+    #[case("+2w", "2026-10-30")]
+    #[case("+1m", "2026-11-16")]
+    #[case("mon", "2026-10-19")]
+    #[case("thursday", "2026-10-22")]
+    #[case("fri", "2026-10-23")]
+    #[case("next fri", "2026-10-23")]
+    #[case("sat", "2026-10-17")]
+    fn should_parse_date(#[case] s: &str, #[case] expected: &str) {
+        assert_eq!(parse_date(s, date("2026-10-16")), Ok(date(expected)));
+    }
+
+    #[rstest]
+    #[case("2026-13-01")]
+    #[case("+3x")]
+    #[case("+d")]
+    #[case("+")]
+    #[case("+日")]
+    #[case("next")]
+    #[case("someday")]
+    fn should_return_error_if_date_is_invalid(#[case] s: &str) {
+        assert_eq!(
+            parse_date(s, date("2026-10-16")),
+            Err(format!("invalid date; {}", s))
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_IO: i32 = 3;
+/// Malformed stored or imported data: JSON, CSV, a bad value in an imported record or an
+/// unknown file layout.
+pub const EXIT_JSON: i32 = 4;
+pub const EXIT_NOT_FOUND: i32 = 5;
+pub const EXIT_CONFLICT: i32 = 6;
+pub const EXIT_LOCKED: i32 = 7;
+
+#[derive(Debug)]
+pub enum Error {
+    /// An I/O failure; `path` names the file it happened on, when known.
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    Json {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    Csv(csv::Error),
+    /// A value on line `line` of an imported CSV or TSV file cannot be read.
+    Record {
+        line: usize,
+        message: String,
+    },
+    Sqlite(rusqlite::Error),
+    /// The stored file's layout or version cannot be read.
+    Schema(String),
+    /// No todo has this id or hash id.
+    NotFound(String),
+    /// A command-line argument was missing or malformed. `option` names the flag whose value
+    /// was at fault, or is empty when the arguments as a whole were wrong.
+    InvalidArgument {
+        option: String,
+        reason: String,
+    },
+    /// A value was rejected by the todo list itself, such as an empty title.
+    Invalid(String),
+    /// The request clashes with the current state, such as finishing a finished todo.
+    Conflict(String),
+    /// Another process holds the store's lock; the message names it.
+    Locked(String),
+}
+
+impl Error {
+    pub fn invalid_argument(option: &str, reason: impl Into<String>) -> Self {
+        Self::InvalidArgument {
+            option: option.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    /// An [`Error::InvalidArgument`] about the arguments as a whole rather than one option.
+    pub fn usage(reason: impl Into<String>) -> Self {
+        Self::invalid_argument("", reason)
+    }
+
+    pub fn not_found(id: impl fmt::Display) -> Self {
+        Self::NotFound(id.to_string())
+    }
+
+    /// For `map_err`: an [`Error::Io`] on `path`.
+    pub fn io(path: impl AsRef<Path>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.as_ref().to_path_buf();
+        move |source| Self::Io {
+            path: Some(path),
+            source,
+        }
+    }
+
+    /// Names `path` in an [`Error::Io`] that names no file yet; other errors are kept as is.
+    pub fn with_path(self, path: impl AsRef<Path>) -> Self {
+        match self {
+            Self::Io { path: None, source } => Self::io(path)(source),
+            x => x,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Io { .. } | Self::Sqlite(_) => EXIT_IO,
+            Self::Json { .. } | Self::Csv(_) | Self::Record { .. } | Self::Schema(_) => EXIT_JSON,
+            Self::NotFound(_) => EXIT_NOT_FOUND,
+            Self::InvalidArgument { .. } => EXIT_USAGE,
+            Self::Invalid(_) => EXIT_FAILURE,
+            Self::Conflict(_) => EXIT_CONFLICT,
+            Self::Locked(_) => EXIT_LOCKED,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io {
+                path: Some(path),
+                source,
+            } => write!(f, "{}: {}", path.display(), source),
+            Self::Io { path: None, source } => write!(f, "{}", source),
+            Self::Json {
+                line: 0, message, ..
+            } => write!(f, "invalid JSON; {}", message),
+            Self::Json {
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "invalid JSON at line {}, column {}; {}",
+                line, column, message
+            ),
+            Self::Csv(err) => write!(f, "invalid CSV; {}", err),
+            Self::Record { line, message } => write!(f, "line {}: {}", line, message),
+            Self::Sqlite(err) => write!(f, "database error; {}", err),
+            Self::Schema(message) | Self::Invalid(message) | Self::Conflict(message) => {
+                write!(f, "{}", message)
+            }
+            Self::NotFound(id) => write!(f, "todo not found; id={}", id),
+            Self::InvalidArgument { option, reason } if option.is_empty() => {
+                write!(f, "{}", reason)
+            }
+            Self::InvalidArgument { option, reason } => write!(f, "{}: {}", option, reason),
+            Self::Locked(holder) => write!(f, "{}", holder),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Csv(err) => Some(err),
+            Self::Sqlite(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io {
+            path: None,
+            source: err,
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        if err.is_io() {
+            return io::Error::from(err).into();
+        }
+
+        let (line, column) = (err.line(), err.column());
+        let message = err.to_string();
+        let suffix = format!(" at line {} column {}", line, column);
+        Self::Json {
+            line,
+            column,
+            message: message
+                .strip_suffix(&suffix)
+                .unwrap_or(&message)
+                .to_string(),
+        }
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(err: csv::Error) -> Self {
+        if err.is_io_error() {
+            return match err.into_kind() {
+                csv::ErrorKind::Io(err) => err.into(),
+                _ => unreachable!(),
+            };
+        }
+        Self::Csv(err)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Self::Sqlite(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[test]
+    fn should_keep_json_position() {
+        let err = Error::from(serde_json::from_str::<Vec<u8>>("[1,\n x]").unwrap_err());
+
+        match &err {
+            Error::Json { line, column, .. } => assert_eq!((*line, *column), (2, 2)),
+            x => panic!("not Json; {:?}", x),
+        }
+        assert_eq!(
+            err.to_string(),
+            "invalid JSON at line 2, column 2; expected value"
+        );
+    }
+
+    #[rstest]
+    #[case(Error::from(io::Error::from(io::ErrorKind::NotFound)), EXIT_IO)]
+    #[case(Error::from(serde_json::from_str::<Vec<u8>>("{").unwrap_err()), EXIT_JSON)]
+    #[case(Error::not_found(3), EXIT_NOT_FOUND)]
+    #[case(Error::invalid_argument("-s", "invalid status; x"), EXIT_USAGE)]
+    #[case(Error::Invalid(String::from("title must not be empty")), EXIT_FAILURE)]
+    #[case(Error::Conflict(String::from("todo 1 is already Done")), EXIT_CONFLICT)]
+    #[case(Error::Locked(String::from("locked")), EXIT_LOCKED)]
+    fn should_map_variants_to_exit_codes(#[case] err: Error, #[case] expected: i32) {
+        assert_eq!(err.exit_code(), expected);
+    }
+
+    #[rstest]
+    #[case(Error::not_found(3), "todo not found; id=3")]
+    #[case(
+        Error::invalid_argument("-s", "invalid status; x"),
+        "-s: invalid status; x"
+    )]
+    #[case(
+        Error::invalid_argument("", "subcommand required"),
+        "subcommand required"
+    )]
+    #[case(
+        Error::io("todo-list.json")(io::Error::other("denied")),
+        "todo-list.json: denied"
+    )]
+    #[case(
+        Error::from(io::Error::other("denied")).with_path("todo-list.json"),
+        "todo-list.json: denied"
+    )]
+    fn should_render_for_cli(#[case] err: Error, #[case] expected: &str) {
+        assert_eq!(err.to_string(), expected);
+    }
+}
@@ -0,0 +1,677 @@
+use crate::args::parse_id;
+use crate::date::{local_date, parse_date, today};
+use crate::todo::{Priority, Status, Todo};
+use chrono::NaiveDate;
+use regex::Regex;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+const FIELDS: &str = "id, parent, status, priority, due, created, label, title or description";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cmp {
+    fn holds(&self, ordering: Ordering) -> bool {
+        match self {
+            Self::Eq => ordering.is_eq(),
+            Self::Ne => ordering.is_ne(),
+            Self::Lt => ordering.is_lt(),
+            Self::Le => ordering.is_le(),
+            Self::Gt => ordering.is_gt(),
+            Self::Ge => ordering.is_ge(),
+        }
+    }
+
+    /// Compares an optional field, where a missing value only satisfies `!=`.
+    fn holds_for<T: Ord>(&self, value: Option<T>, other: &T) -> bool {
+        match value {
+            Some(x) => self.holds(x.cmp(other)),
+            None => *self == Self::Ne,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TextMatch {
+    /// Contains the text, ignoring case; held lowercased.
+    Contains(String),
+    Equals(String),
+    Regex(Regex),
+}
+
+impl TextMatch {
+    fn matches(&self, s: &str) -> bool {
+        match self {
+            Self::Contains(x) => s.to_lowercase().contains(x),
+            Self::Equals(x) => s == x,
+            Self::Regex(re) => re.is_match(s),
+        }
+    }
+}
+
+/// A condition on one field. `None` stands for a missing parent or due date.
+#[derive(Debug, Clone)]
+pub enum Predicate {
+    Id(Cmp, usize),
+    Parent(Cmp, Option<usize>),
+    Status(Cmp, Status),
+    Priority(Cmp, Priority),
+    Due(Cmp, Option<NaiveDate>),
+    Created(Cmp, NaiveDate),
+    /// Matches if any label does.
+    Label(TextMatch),
+    Title(TextMatch),
+    Description(TextMatch),
+}
+
+impl Predicate {
+    pub fn matches(&self, todo: &Todo) -> bool {
+        match self {
+            Self::Id(cmp, id) => cmp.holds(todo.id.cmp(id)),
+            Self::Parent(cmp, parent) => cmp.holds(todo.parent.cmp(parent)),
+            Self::Status(cmp, status) => cmp.holds(todo.status.cmp(status)),
+            Self::Priority(cmp, priority) => cmp.holds(todo.priority.cmp(priority)),
+            Self::Due(cmp, None) => cmp.holds(todo.due.cmp(&None)),
+            Self::Due(cmp, Some(due)) => cmp.holds_for(todo.due, due),
+            Self::Created(cmp, date) => cmp.holds_for(todo.created_at.map(local_date), date),
+            Self::Label(m) => todo.labels.iter().any(|x| m.matches(x)),
+            Self::Title(m) => m.matches(&todo.title),
+            Self::Description(m) => m.matches(&todo.description),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Predicate(Predicate),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub fn matches(&self, todo: &Todo) -> bool {
+        match self {
+            Self::Predicate(x) => x.matches(todo),
+            Self::Not(x) => !x.matches(todo),
+            Self::And(a, b) => a.matches(todo) && b.matches(todo),
+            Self::Or(a, b) => a.matches(todo) || b.matches(todo),
+        }
+    }
+}
+
+/// Why a query did not parse, and where: `column` counts characters from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub query: String,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at column {}\n    {}\n    {}^",
+            self.message,
+            self.column,
+            self.query,
+            " ".repeat(self.column - 1)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    /// `<field><op><value>`, with the columns the operator and value start at.
+    Term {
+        field: String,
+        op: String,
+        op_column: usize,
+        value: String,
+        value_column: usize,
+    },
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::LParen => write!(f, "`(`"),
+            Self::RParen => write!(f, "`)`"),
+            Self::And => write!(f, "`AND`"),
+            Self::Or => write!(f, "`OR`"),
+            Self::Not => write!(f, "`NOT`"),
+            Self::Term { field, op, .. } => write!(f, "`{}{}`", field, op),
+        }
+    }
+}
+
+const OPS: [&str; 8] = ["!=", "<=", ">=", ":", "=", "<", ">", "~"];
+
+/// Splits a query into tokens, each with the column it starts at.
+fn lex(query: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let chars = query.chars().collect::<Vec<_>>();
+    let error = |column: usize, message: String| ParseError {
+        query: query.to_string(),
+        column,
+        message,
+    };
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        match chars[i] {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => {
+                tokens.push((Token::LParen, start + 1));
+                i += 1;
+                continue;
+            }
+            ')' => {
+                tokens.push((Token::RParen, start + 1));
+                i += 1;
+                continue;
+            }
+            c if is_word(c) => (),
+            c => return Err(error(start + 1, format!("unexpected `{}`", c))),
+        }
+
+        while i < chars.len() && is_word(chars[i]) {
+            i += 1;
+        }
+        let word = chars[start..i].iter().collect::<String>();
+        let rest = chars[i..].iter().collect::<String>();
+        let op = match OPS.iter().find(|x| rest.starts_with(*x)) {
+            Some(x) => x.to_string(),
+            None => {
+                let keyword = match &word.to_uppercase()[..] {
+                    "AND" => Some(Token::And),
+                    "OR" => Some(Token::Or),
+                    "NOT" => Some(Token::Not),
+                    _ => None,
+                };
+                match keyword {
+                    Some(x) => tokens.push((x, start + 1)),
+                    None => {
+                        return Err(error(
+                            i + 1,
+                            format!("expected an operator (: = != < <= > >= ~) after `{}`", word),
+                        ))
+                    }
+                }
+                continue;
+            }
+        };
+        let op_column = i + 1;
+        i += op.chars().count();
+
+        let value_column = i + 1;
+        let mut value = String::new();
+        if chars.get(i) == Some(&'"') {
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Some('"') => break,
+                    Some('\\') if i + 1 < chars.len() => {
+                        value.push(chars[i + 1]);
+                        i += 2;
+                    }
+                    Some(&c) => {
+                        value.push(c);
+                        i += 1;
+                    }
+                    None => return Err(error(value_column, String::from("unterminated quote"))),
+                }
+            }
+            i += 1;
+        } else {
+            // Parentheses inside a value, as in `title~(fix|bug)`, are part of it as long
+            // as they balance; an unmatched `)` closes the enclosing group instead.
+            let mut depth = 0;
+            while let Some(&c) = chars.get(i) {
+                match c {
+                    c if c.is_whitespace() => break,
+                    ')' if depth == 0 => break,
+                    ')' => depth -= 1,
+                    '(' => depth += 1,
+                    _ => (),
+                }
+                value.push(c);
+                i += 1;
+            }
+            if value.is_empty() {
+                return Err(error(
+                    value_column,
+                    format!("expected a value after `{}{}`", word, op),
+                ));
+            }
+        }
+
+        tokens.push((
+            Token::Term {
+                field: word,
+                op,
+                op_column,
+                value,
+                value_column,
+            },
+            start + 1,
+        ));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    query: &'a str,
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            query: self.query.to_string(),
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(x, _)| x)
+    }
+
+    /// Column of the next token, or just past the end of the query.
+    fn column(&self) -> usize {
+        match self.tokens.get(self.pos) {
+            Some((_, column)) => *column,
+            None => self.query.chars().count() + 1,
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    /// Conditions next to each other are joined by an implicit `AND`.
+    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_not()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => self.pos += 1,
+                Some(Token::Not | Token::LParen | Token::Term { .. }) => (),
+                _ => break,
+            }
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, ParseError> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        let column = self.column();
+        let token = match self.tokens.get(self.pos) {
+            Some((x, _)) => x.clone(),
+            None => return Err(self.error(column, "expected a condition")),
+        };
+        self.pos += 1;
+
+        match token {
+            Token::LParen => {
+                let expr = self.parse_or()?;
+                match self.peek() {
+                    Some(Token::RParen) => {
+                        self.pos += 1;
+                        Ok(expr)
+                    }
+                    _ => Err(self.error(self.column(), "expected `)`")),
+                }
+            }
+            Token::Term {
+                field,
+                op,
+                op_column,
+                value,
+                value_column,
+            } => {
+                let term = Term {
+                    field: &field,
+                    op: &op,
+                    value: &value,
+                };
+                term.predicate().map_err(|(at, message)| {
+                    let column = match at {
+                        At::Field => column,
+                        At::Op => op_column,
+                        At::Value => value_column,
+                    };
+                    self.error(column, message)
+                })
+            }
+            x => Err(self.error(column, format!("expected a condition before {}", x))),
+        }
+    }
+}
+
+/// The part of a term an error is about.
+enum At {
+    Field,
+    Op,
+    Value,
+}
+
+struct Term<'a> {
+    field: &'a str,
+    op: &'a str,
+    value: &'a str,
+}
+
+impl Term<'_> {
+    fn cmp(&self) -> Result<Cmp, (At, String)> {
+        Ok(match self.op {
+            ":" | "=" => Cmp::Eq,
+            "!=" => Cmp::Ne,
+            "<" => Cmp::Lt,
+            "<=" => Cmp::Le,
+            ">" => Cmp::Gt,
+            ">=" => Cmp::Ge,
+            _ => return Err(self.invalid_op()),
+        })
+    }
+
+    /// Like [`Term::cmp`], for fields that are only equal or not.
+    fn equality(&self) -> Result<Cmp, (At, String)> {
+        match self.cmp()? {
+            x @ (Cmp::Eq | Cmp::Ne) => Ok(x),
+            _ => Err(self.invalid_op()),
+        }
+    }
+
+    fn invalid_op(&self) -> (At, String) {
+        (
+            At::Op,
+            format!("`{}` does not apply to {}", self.op, self.field),
+        )
+    }
+
+    fn value<T>(&self, parse: impl Fn(&str) -> Result<T, String>) -> Result<T, (At, String)> {
+        parse(self.value).map_err(|x| (At::Value, x))
+    }
+
+    /// `exact` makes `:` match whole values, as for labels.
+    fn text(&self, exact: bool) -> Result<Expr, (At, String)> {
+        let m = match self.op {
+            ":" if !exact => TextMatch::Contains(self.value.to_lowercase()),
+            ":" | "=" | "!=" => TextMatch::Equals(self.value.to_string()),
+            "~" => TextMatch::Regex(
+                self.value(|x| Regex::new(x).map_err(|_| format!("invalid regex; {}", x)))?,
+            ),
+            _ => return Err(self.invalid_op()),
+        };
+        let predicate = Expr::Predicate(match self.field {
+            "label" | "labels" => Predicate::Label(m),
+            "title" => Predicate::Title(m),
+            _ => Predicate::Description(m),
+        });
+
+        Ok(match self.op {
+            "!=" => Expr::Not(Box::new(predicate)),
+            _ => predicate,
+        })
+    }
+
+    fn predicate(&self) -> Result<Expr, (At, String)> {
+        let parse_date = |x: &str| parse_date(x, today());
+
+        let predicate = match self.field {
+            "id" => Predicate::Id(self.cmp()?, self.value(parse_id)?),
+            "parent" => Predicate::Parent(
+                self.equality()?,
+                match self.value {
+                    "none" => None,
+                    _ => Some(self.value(parse_id)?),
+                },
+            ),
+            "status" => Predicate::Status(self.cmp()?, self.value(str::parse)?),
+            "priority" => Predicate::Priority(self.cmp()?, self.value(str::parse)?),
+            "due" => match self.value {
+                "none" => Predicate::Due(self.equality()?, None),
+                _ => Predicate::Due(self.cmp()?, Some(self.value(parse_date)?)),
+            },
+            "created" => Predicate::Created(self.cmp()?, self.value(parse_date)?),
+            "label" | "labels" => return self.text(true),
+            "title" | "description" => return self.text(false),
+            x => {
+                return Err((
+                    At::Field,
+                    format!("unknown field `{}`; expected {}", x, FIELDS),
+                ))
+            }
+        };
+
+        Ok(Expr::Predicate(predicate))
+    }
+}
+
+/// A parsed query such as `status:Done label:infra due<2026-11-01`, kept with its text.
+///
+/// A condition is `<field><op><value>`. `id`, `status`, `priority`, `due` and `created`
+/// compare with `:` or `=`, `!=`, `<`, `<=`, `>` and `>=`; dates may be relative, and
+/// `due:none` matches todos without a due date (`parent:none` likewise). `title` and
+/// `description` contain a value with `:` (ignoring case), equal it with `=` and match
+/// a regex with `~`; `label` does the same for any label, except that `:` matches whole
+/// labels. Values with spaces go in double quotes. Conditions combine with `AND` (or
+/// nothing), `OR` and `NOT`, in that order of precedence looser to tighter, and with
+/// parentheses.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    query: String,
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn parse(query: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            query,
+            tokens: lex(query)?,
+            pos: 0,
+        };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(parser.error(parser.column(), format!("unexpected {}", token)));
+        }
+
+        Ok(Self {
+            query: query.to_string(),
+            expr,
+        })
+    }
+
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    pub fn matches(&self, todo: &Todo) -> bool {
+        self.expr.matches(todo)
+    }
+}
+
+impl PartialEq for Filter {
+    fn eq(&self, other: &Self) -> bool {
+        self.query == other.query
+    }
+}
+
+impl Eq for Filter {}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.query)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s).map_err(|x| x.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::labels;
+    use crate::todo::{PartialTodo, TodoList, TodoPatch};
+    use rstest::*;
+
+    /// 0: "Rotate keys", infra, due 2026-10-20, Done
+    /// 1: "Fix flaky test", ci and infra, high, subtask of 0
+    /// 2: "Write notes", no labels, due 2026-11-05, urgent
+    fn create_todo_list() -> TodoList {
+        let mut todo_list = TodoList::new(Vec::new().into_iter());
+        for (title, label, due, priority, parent) in [
+            (
+                "Rotate keys",
+                &["infra"][..],
+                "2026-10-20",
+                Priority::Normal,
+                None,
+            ),
+            (
+                "Fix flaky test",
+                &["ci", "infra"],
+                "",
+                Priority::High,
+                Some(0),
+            ),
+            ("Write notes", &[], "2026-11-05", Priority::Urgent, None),
+        ] {
+            todo_list.add(&PartialTodo {
+                title: String::from(title),
+                description: format!("about {}", title.to_lowercase()),
+                labels: labels(label),
+                due: NaiveDate::parse_from_str(due, crate::date::DATE_FORMAT).ok(),
+                priority,
+                parent,
+                ..Default::default()
+            });
+        }
+        let patch = TodoPatch {
+            status: Some(Status::Done),
+            ..Default::default()
+        };
+        todo_list.update(0, &patch).unwrap();
+        todo_list
+    }
+
+    #[rstest]
+    #[case("status:Done", vec![0])]
+    #[case("status:done", vec![0])]
+    #[case("status<Done", vec![1, 2])]
+    #[case("status:ToDo label:infra", vec![1])]
+    #[case("status:ToDo AND label:infra", vec![1])]
+    #[case("label:ci OR due>2026-11-01", vec![1, 2])]
+    #[case("label:inf", vec![])]
+    #[case("label~^inf", vec![0, 1])]
+    #[case("label!=infra", vec![2])]
+    #[case("NOT label:infra", vec![2])]
+    #[case("not (id=0 or id=2)", vec![1])]
+    #[case("id>=1 id<2", vec![1])]
+    #[case("due<2026-11-01", vec![0])]
+    #[case("due:none", vec![1])]
+    #[case("due!=none", vec![0, 2])]
+    #[case("due!=2026-10-20", vec![1, 2])]
+    #[case("priority>=high", vec![1, 2])]
+    #[case("parent:0", vec![1])]
+    #[case("parent:none", vec![0, 2])]
+    #[case("title:FLAKY", vec![1])]
+    #[case("title=\"Write notes\"", vec![2])]
+    #[case("title~(Fix|Write)", vec![1, 2])]
+    #[case("(title~(keys|notes))", vec![0, 2])]
+    #[case("description:\"about write\"", vec![2])]
+    #[case("created<=today created>=-1d", vec![0, 1, 2])]
+    #[case("created<today", vec![])]
+    #[case("label:infra OR label:ci AND status:Done", vec![0, 1])]
+    #[case("(label:infra OR label:ci) AND status:Done", vec![0])]
+    fn should_match_todos(#[case] query: &str, #[case] expected: Vec<usize>) {
+        let todo_list = create_todo_list();
+        let filter = Filter::parse(query).unwrap();
+
+        let ids = todo_list
+            .list()
+            .iter()
+            .filter(|x| filter.matches(x))
+            .map(|x| x.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, expected);
+    }
+
+    #[rstest]
+    #[case("", 1, "expected a condition")]
+    #[case("status:Done AND", 16, "expected a condition")]
+    #[case(
+        "status",
+        7,
+        "expected an operator (: = != < <= > >= ~) after `status`"
+    )]
+    #[case("status: Done", 8, "expected a value after `status:`")]
+    #[case("status:Doing", 8, "invalid status; Doing")]
+    #[case("colour:red", 1, "unknown field `colour`; expected id, parent, status, priority, due, created, label, title or description")]
+    #[case("label:x due~2026", 12, "`~` does not apply to due")]
+    #[case("parent<3", 7, "`<` does not apply to parent")]
+    #[case("due<none", 4, "`<` does not apply to due")]
+    #[case("id:x", 4, "invalid id; x")]
+    #[case("title~(", 7, "invalid regex; (")]
+    #[case("(id:1 OR id:2", 14, "expected `)`")]
+    #[case("id:1)", 5, "unexpected `)`")]
+    #[case("OR id:1", 1, "expected a condition before `OR`")]
+    #[case("title:\"open", 7, "unterminated quote")]
+    #[case("id:1 & id:2", 6, "unexpected `&`")]
+    fn should_point_at_error(#[case] query: &str, #[case] column: usize, #[case] message: &str) {
+        let error = Filter::parse(query).unwrap_err();
+
+        assert_eq!((error.column, &error.message[..]), (column, message));
+    }
+
+    #[test]
+    fn should_show_error_under_query() {
+        let message = "status:Doing".parse::<Filter>().unwrap_err();
+
+        assert_eq!(
+            message,
+            "invalid status; Doing at column 8\n    status:Doing\n           ^"
+        );
+    }
+}
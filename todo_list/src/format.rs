@@ -0,0 +1,342 @@
+use crate::table::{render, terminal_width, Column};
+use crate::todo::{Status, Todo};
+use crate::Error;
+use std::io::Write;
+use std::str::FromStr;
+
+/// Writes todos to `out` in one output format.
+/// Table, CSV and TSV honour `columns`; JSON and NDJSON always write whole todos.
+pub trait Formatter {
+    fn write(&self, todos: &[&Todo], columns: &[Column], out: &mut dyn Write) -> Result<(), Error>;
+}
+
+pub struct TableFormatter {
+    pub width: usize,
+    pub wrap: bool,
+    pub date_format: String,
+}
+
+impl Formatter for TableFormatter {
+    fn write(&self, todos: &[&Todo], columns: &[Column], out: &mut dyn Write) -> Result<(), Error> {
+        writeln!(
+            out,
+            "{}",
+            render(
+                todos.iter().copied(),
+                columns,
+                self.width,
+                self.wrap,
+                &self.date_format
+            )
+        )?;
+
+        Ok(())
+    }
+}
+
+pub struct JsonFormatter;
+
+impl Formatter for JsonFormatter {
+    fn write(
+        &self,
+        todos: &[&Todo],
+        _columns: &[Column],
+        out: &mut dyn Write,
+    ) -> Result<(), Error> {
+        serde_json::to_writer_pretty(&mut *out, todos)?;
+        writeln!(out)?;
+
+        Ok(())
+    }
+}
+
+pub struct NdjsonFormatter;
+
+impl Formatter for NdjsonFormatter {
+    fn write(
+        &self,
+        todos: &[&Todo],
+        _columns: &[Column],
+        out: &mut dyn Write,
+    ) -> Result<(), Error> {
+        for todo in todos {
+            serde_json::to_writer(&mut *out, todo)?;
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// CSV (`,`) or TSV (`\t`) with a header row; fields containing the delimiter,
+/// quotes or newlines are quoted.
+pub struct DelimitedFormatter {
+    pub delimiter: u8,
+}
+
+impl Formatter for DelimitedFormatter {
+    fn write(&self, todos: &[&Todo], columns: &[Column], out: &mut dyn Write) -> Result<(), Error> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .from_writer(out);
+
+        writer.write_record(columns.iter().map(|x| x.header()))?;
+        for todo in todos {
+            writer.write_record(columns.iter().map(|x| x.value(todo)))?;
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Markdown checklist grouped by label set: a todo labelled `a` and `b` sits under
+/// `## a` / `### b`, and unlabelled todos come first without a heading.
+/// Only `Done` is checked, and descriptions are indented under their item.
+pub struct MarkdownFormatter;
+
+impl Formatter for MarkdownFormatter {
+    fn write(
+        &self,
+        todos: &[&Todo],
+        _columns: &[Column],
+        out: &mut dyn Write,
+    ) -> Result<(), Error> {
+        let mut groups = todos.iter().map(|x| &x.labels).collect::<Vec<_>>();
+        groups.sort();
+        groups.dedup();
+
+        let mut path: Vec<&str> = Vec::new();
+        // Headings end with a blank line, so only items need one before the next heading.
+        let mut after_item = false;
+        for labels in groups {
+            let items = todos
+                .iter()
+                .filter(|x| &x.labels == labels)
+                .collect::<Vec<_>>();
+            let labels = labels.iter().map(String::as_str).collect::<Vec<_>>();
+
+            let common = path.iter().zip(&labels).take_while(|(a, b)| a == b).count();
+            for (depth, label) in labels.iter().enumerate().skip(common) {
+                if after_item {
+                    writeln!(out)?;
+                }
+                writeln!(out, "{} {}\n", "#".repeat((depth + 2).min(6)), label)?;
+                after_item = false;
+            }
+            for todo in items {
+                let mark = if todo.status == Status::Done {
+                    'x'
+                } else {
+                    ' '
+                };
+                writeln!(out, "- [{}] {}", mark, todo.title)?;
+                for line in todo.description.lines().filter(|x| !x.trim().is_empty()) {
+                    writeln!(out, "  {}", line)?;
+                }
+            }
+            path = labels;
+            after_item = true;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+    Ndjson,
+    Csv,
+    Tsv,
+    Markdown,
+}
+
+impl Format {
+    /// `wrap` and `date_format` only affect the table; the other formats keep ISO dates
+    /// so they can be imported again.
+    pub fn formatter(&self, wrap: bool, date_format: &str) -> Box<dyn Formatter> {
+        match self {
+            Self::Table => Box::new(TableFormatter {
+                width: terminal_width(),
+                wrap,
+                date_format: date_format.to_string(),
+            }),
+            Self::Json => Box::new(JsonFormatter),
+            Self::Ndjson => Box::new(NdjsonFormatter),
+            Self::Csv => Box::new(DelimitedFormatter { delimiter: b',' }),
+            Self::Tsv => Box::new(DelimitedFormatter { delimiter: b'\t' }),
+            Self::Markdown => Box::new(MarkdownFormatter),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            "ndjson" => Ok(Self::Ndjson),
+            "csv" => Ok(Self::Csv),
+            "tsv" => Ok(Self::Tsv),
+            "markdown" | "md" => Ok(Self::Markdown),
+            _ => Err(format!("invalid format; {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::DATE_FORMAT;
+    use crate::test_utils::labels;
+    use crate::todo::{Status, TodoList};
+    use rstest::*;
+
+    fn create_todo_list() -> TodoList {
+        TodoList::new(
+            vec![
+                Todo {
+                    id: 0,
+                    title: String::from("title0"),
+                    description: String::from("a, b\nc"),
+                    status: Status::ToDo,
+                    labels: labels(&[]),
+                    ..Default::default()
+                },
+                Todo {
+                    id: 1,
+                    title: String::from("say \"hi\""),
+                    description: String::from("tab\there"),
+                    status: Status::Done,
+                    labels: labels(&["label1"]),
+                    ..Default::default()
+                },
+            ]
+            .into_iter(),
+        )
+    }
+
+    fn write(format: Format, columns: &[Column]) -> String {
+        let todo_list = create_todo_list();
+        let todos = todo_list.list().iter().collect::<Vec<_>>();
+        let mut out = Vec::new();
+
+        format
+            .formatter(false, DATE_FORMAT)
+            .write(&todos, columns, &mut out)
+            .unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn should_write_csv_with_quoted_fields() {
+        assert_eq!(
+            write(Format::Csv, &Column::DEFAULT),
+            "id,title,description,status,priority,due,labels\n\
+             0,title0,\"a, b\nc\",ToDo,Normal,,\n\
+             1,\"say \"\"hi\"\"\",tab\there,Done,Normal,,label1\n"
+        );
+    }
+
+    #[test]
+    fn should_write_tsv_with_selected_columns() {
+        assert_eq!(
+            write(Format::Tsv, &[Column::Id, Column::Description]),
+            "id\tdescription\n\
+             0\t\"a, b\nc\"\n\
+             1\t\"tab\there\"\n"
+        );
+    }
+
+    #[test]
+    fn should_write_json_readable_as_todos() {
+        let todos: Vec<Todo> =
+            serde_json::from_str(&write(Format::Json, &Column::DEFAULT)).unwrap();
+
+        assert_eq!(TodoList::new(todos.into_iter()), create_todo_list());
+    }
+
+    #[test]
+    fn should_write_one_json_object_per_line_as_ndjson() {
+        let out = write(Format::Ndjson, &Column::DEFAULT);
+
+        let todos = out
+            .lines()
+            .map(|x| serde_json::from_str::<Todo>(x).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(TodoList::new(todos.into_iter()), create_todo_list());
+    }
+
+    #[test]
+    fn should_write_markdown_checklist_grouped_by_label() {
+        let mut todo_list = create_todo_list();
+        for (title, labels) in [
+            ("title2", labels(&["label1", "label2"])),
+            ("title3", labels(&["label2"])),
+            ("title4", labels(&["label3", "label4"])),
+        ] {
+            todo_list.add(&crate::todo::PartialTodo {
+                title: String::from(title),
+                description: String::from(""),
+                labels,
+                ..Default::default()
+            });
+        }
+        let todos = todo_list.list().iter().collect::<Vec<_>>();
+        let mut out = Vec::new();
+
+        MarkdownFormatter.write(&todos, &[], &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "- [ ] title0\n  \
+             a, b\n  \
+             c\n\
+             \n\
+             ## label1\n\
+             \n\
+             - [x] say \"hi\"\n  \
+             tab\there\n\
+             \n\
+             ### label2\n\
+             \n\
+             - [ ] title2\n\
+             \n\
+             ## label2\n\
+             \n\
+             - [ ] title3\n\
+             \n\
+             ## label3\n\
+             \n\
+             ### label4\n\
+             \n\
+             - [ ] title4\n"
+        );
+    }
+
+    #[test]
+    fn should_write_table() {
+        let out = write(Format::Table, &[Column::Id, Column::Title]);
+
+        assert!(out.starts_with("id title"), "out={out}");
+        assert_eq!(out.lines().count(), 4);
+    }
+
+    #[rstest]
+    #[case("table", Ok(Format::Table))]
+    #[case("json", Ok(Format::Json))]
+    #[case("ndjson", Ok(Format::Ndjson))]
+    #[case("csv", Ok(Format::Csv))]
+    #[case("tsv", Ok(Format::Tsv))]
+    #[case("markdown", Ok(Format::Markdown))]
+    #[case("md", Ok(Format::Markdown))]
+    #[case("xml", Err(String::from("invalid format; xml")))]
+    fn should_parse_format(#[case] s: &str, #[case] expected: Result<Format, String>) {
+        assert_eq!(s.parse::<Format>(), expected);
+    }
+}
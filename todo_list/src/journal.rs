@@ -0,0 +1,620 @@
+use crate::date::TIMESTAMP_FORMAT;
+use crate::lock::FileLock;
+use crate::migrate::Migration;
+use crate::store::TodoStore;
+use crate::todo::{Todo, TodoList};
+use crate::Error;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Operations kept in the journal; older ones are dropped and can no longer be undone.
+/// The file is only compacted every `JOURNAL_LIMIT` operations, so it may hold up to twice
+/// as many until then.
+pub const JOURNAL_LIMIT: usize = 100;
+
+/// The journal recording changes to the store at `path`.
+pub fn journal_path(path: &Path) -> PathBuf {
+    let mut journal_path = path.as_os_str().to_owned();
+    journal_path.push(".journal");
+    PathBuf::from(journal_path)
+}
+
+/// One todo before and after an operation; `None` means it did not exist.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Change {
+    pub id: usize,
+    pub before: Option<Todo>,
+    pub after: Option<Todo>,
+}
+
+/// Everything one command changed. Undone entries stay at the end of the journal until
+/// redone, or until the next recorded operation drops them.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Entry {
+    /// Counts the operations recorded in the journal, starting from 1.
+    #[serde(default)]
+    pub seq: usize,
+    pub at: DateTime<Utc>,
+    pub command: String,
+    pub changes: Vec<Change>,
+    #[serde(default)]
+    pub undone: bool,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}  {} ({} todo(s))",
+            self.at.with_timezone(&Local).format(TIMESTAMP_FORMAT),
+            self.command,
+            self.changes.len()
+        )
+    }
+}
+
+/// Entries stored one JSON object per line, oldest first. A command that writes several
+/// times appends a line per write, all with the same `seq` and `at`.
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Journal { path: path.into() }
+    }
+
+    /// Reads the last [`JOURNAL_LIMIT`] entries, joining the lines of each and leaving out
+    /// undone entries a later operation dropped; a missing journal has none.
+    pub fn load(&self) -> Result<Vec<Entry>, Error> {
+        let file = match File::open(&self.path) {
+            Ok(x) => x,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(Error::io(&self.path)(err)),
+        };
+
+        let mut entries = Vec::<Entry>::new();
+        // Where each todo's change is in the last entry, for joining its further lines.
+        let mut positions = HashMap::<usize, usize>::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: Entry = serde_json::from_str(&line)?;
+            match entries.last_mut() {
+                Some(last) if last.seq == entry.seq && last.at == entry.at => {
+                    for change in entry.changes {
+                        match positions.get(&change.id) {
+                            Some(&i) => last.changes[i].after = change.after,
+                            None => {
+                                positions.insert(change.id, last.changes.len());
+                                last.changes.push(change);
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    positions = entry
+                        .changes
+                        .iter()
+                        .enumerate()
+                        .map(|(i, x)| (x.id, i))
+                        .collect();
+                    entries.push(entry);
+                }
+            }
+        }
+
+        if let Some(last) = entries.iter().rposition(|x| !x.undone) {
+            let mut i = 0;
+            entries.retain(|x| {
+                i += 1;
+                i > last || !x.undone
+            });
+        }
+        let excess = entries.len().saturating_sub(JOURNAL_LIMIT);
+        entries.drain(..excess);
+
+        Ok(entries)
+    }
+
+    /// The `seq` of the last line, or 0 if there is none. Only the last line is read, from
+    /// the end of the file.
+    fn last_seq(&self) -> Result<usize, Error> {
+        #[derive(Deserialize)]
+        struct Line {
+            #[serde(default)]
+            seq: usize,
+        }
+
+        let mut file = match File::open(&self.path) {
+            Ok(x) => x,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(Error::io(&self.path)(err)),
+        };
+        let mut chunks = Vec::new();
+        let mut pos = file.metadata()?.len();
+        let mut in_line = false;
+        'read: while pos > 0 {
+            let start = pos.saturating_sub(4096);
+            let mut chunk = vec![0; (pos - start) as usize];
+            file.seek(SeekFrom::Start(start))?;
+            file.read_exact(&mut chunk)?;
+            pos = start;
+            for i in (0..chunk.len()).rev() {
+                if chunk[i] == b'\n' && in_line {
+                    chunks.push(chunk.split_off(i + 1));
+                    break 'read;
+                }
+                in_line |= !chunk[i].is_ascii_whitespace();
+            }
+            chunks.push(chunk);
+        }
+        if !in_line {
+            return Ok(0);
+        }
+
+        let line = chunks.into_iter().rev().flatten().collect::<Vec<_>>();
+        Ok(serde_json::from_slice::<Line>(&line)?.seq)
+    }
+
+    /// Adds `entry` as a line at the end of the journal.
+    fn append(&self, entry: &Entry) -> Result<(), Error> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(Error::io(&self.path))?;
+        let mut buf = BufWriter::new(file);
+        serde_json::to_writer(&mut buf, entry)?;
+        writeln!(buf)?;
+        buf.flush()?;
+        buf.get_ref().sync_all()?;
+
+        Ok(())
+    }
+
+    /// Replaces the journal with `entries` through a temporary file, like the JSON store.
+    pub fn save(&self, entries: &[Entry]) -> Result<(), Error> {
+        let mut tmp_path = self.path.as_os_str().to_owned();
+        tmp_path.push(format!(".{}.tmp", std::process::id()));
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let res = Self::write(entries, &tmp_path).and_then(|()| {
+            fs::rename(&tmp_path, &self.path)?;
+            Ok(())
+        });
+        if res.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+
+        res.map_err(|x| x.with_path(&self.path))
+    }
+
+    fn write(entries: &[Entry], path: &Path) -> Result<(), Error> {
+        let mut buf = BufWriter::new(File::create(path)?);
+        for entry in entries {
+            serde_json::to_writer(&mut buf, entry)?;
+            writeln!(buf)?;
+        }
+        buf.flush()?;
+        buf.get_ref().sync_all()?;
+
+        Ok(())
+    }
+}
+
+/// A store whose writes are journaled, one entry per command however many writes it makes,
+/// so [`JournaledStore::undo`] and [`JournaledStore::redo`] can revert and reapply them.
+///
+/// Only the todos a write names are journaled, each against how it was last loaded or
+/// written, so a write costs no more than the store's own. A subcommand must therefore
+/// name every todo it changes, even where the store would write it anyway.
+pub struct JournaledStore {
+    inner: Box<dyn TodoStore>,
+    journal: Journal,
+    command: String,
+    /// The `seq` and time of the entry this command records, once it has written.
+    entry: Option<(usize, DateTime<Utc>)>,
+    /// Todos by id as last loaded or written; `None` until the store is first loaded.
+    todos: RefCell<Option<HashMap<usize, Todo>>>,
+}
+
+impl JournaledStore {
+    /// `command` describes the running command in the entries it records.
+    pub fn new(inner: Box<dyn TodoStore>, journal: Journal, command: &str) -> Self {
+        JournaledStore {
+            inner,
+            journal,
+            command: command.to_string(),
+            entry: None,
+            todos: RefCell::new(None),
+        }
+    }
+
+    fn remember(&self, todo_list: &TodoList) {
+        let todos = todo_list.list().iter().map(|x| (x.id, x.clone())).collect();
+        *self.todos.borrow_mut() = Some(todos);
+    }
+
+    pub fn entries(&self) -> Result<Vec<Entry>, Error> {
+        self.journal.load()
+    }
+
+    /// Runs `write` against the inner store and journals the todos with `ids`, or every
+    /// todo if it is `None`.
+    fn journaled(
+        &mut self,
+        todo_list: &TodoList,
+        ids: Option<&[usize]>,
+        write: impl FnOnce(&mut dyn TodoStore) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        if self.todos.borrow().is_none() {
+            self.remember(&self.inner.load_or_new()?);
+        }
+        write(self.inner.as_mut())?;
+
+        let changes = self.changes(todo_list, ids);
+        self.record(changes)
+    }
+
+    /// The todos with `ids`, or every todo, that differ between `todo_list` and how they
+    /// were last loaded or written, which is then updated to `todo_list`.
+    fn changes(&self, todo_list: &TodoList, ids: Option<&[usize]>) -> Vec<Change> {
+        let mut todos = self.todos.borrow_mut();
+        let todos = todos.get_or_insert_with(HashMap::new);
+        let (ids, after) = match ids {
+            Some(ids) => {
+                let ids = ids.iter().copied().collect::<BTreeSet<_>>();
+                let after = todo_list
+                    .list()
+                    .iter()
+                    .filter(|x| ids.contains(&x.id))
+                    .map(|x| (x.id, x))
+                    .collect::<HashMap<_, _>>();
+                (ids, after)
+            }
+            None => {
+                let after = todo_list
+                    .list()
+                    .iter()
+                    .map(|x| (x.id, x))
+                    .collect::<HashMap<_, _>>();
+                let ids = todos.keys().chain(after.keys()).copied().collect();
+                (ids, after)
+            }
+        };
+
+        let mut changes = Vec::new();
+        for id in ids {
+            let after = after.get(&id).map(|&x| x.clone());
+            let before = match &after {
+                Some(x) => todos.insert(id, x.clone()),
+                None => todos.remove(&id),
+            };
+            if before != after {
+                changes.push(Change { id, before, after });
+            }
+        }
+
+        changes
+    }
+
+    /// Appends `changes` to the journal, as part of this command's entry if it has one.
+    /// Starting every [`JOURNAL_LIMIT`]th entry also compacts the journal.
+    fn record(&mut self, changes: Vec<Change>) -> Result<(), Error> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let (seq, at, compact) = match self.entry {
+            Some((seq, at)) => (seq, at, false),
+            None => {
+                let seq = self.journal.last_seq()? + 1;
+                let at = Utc::now();
+                self.entry = Some((seq, at));
+                (seq, at, seq % JOURNAL_LIMIT == 0)
+            }
+        };
+        self.journal.append(&Entry {
+            seq,
+            at,
+            command: self.command.clone(),
+            changes,
+            undone: false,
+        })?;
+        if compact {
+            self.journal.save(&self.journal.load()?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reverts the last `count` operations not yet undone, newest first, and returns them.
+    pub fn undo(&mut self, count: usize) -> Result<Vec<Entry>, Error> {
+        self.replay(count, true)
+    }
+
+    /// Reapplies the last `count` undone operations, oldest first, and returns them.
+    pub fn redo(&mut self, count: usize) -> Result<Vec<Entry>, Error> {
+        self.replay(count, false)
+    }
+
+    /// Fails without changing anything if a todo no longer matches the journal, e.g.
+    /// because the store was edited without it.
+    fn replay(&mut self, count: usize, undo: bool) -> Result<Vec<Entry>, Error> {
+        let _lock = self.inner.lock()?;
+        let mut entries = self.journal.load()?;
+        let indices = if undo {
+            (0..entries.len())
+                .rev()
+                .filter(|&i| !entries[i].undone)
+                .take(count)
+                .collect::<Vec<_>>()
+        } else {
+            (0..entries.len())
+                .filter(|&i| entries[i].undone)
+                .take(count)
+                .collect::<Vec<_>>()
+        };
+        if indices.is_empty() {
+            let action = if undo { "undo" } else { "redo" };
+            return Err(Error::Invalid(format!("nothing to {}", action)));
+        }
+
+        let mut todo_list = self.inner.load_or_new()?;
+        let mut ids = BTreeSet::new();
+        for &i in &indices {
+            let entry = &mut entries[i];
+            for change in &entry.changes {
+                let (from, to) = if undo {
+                    (&change.after, &change.before)
+                } else {
+                    (&change.before, &change.after)
+                };
+                if todo_list.get(change.id) != from.as_ref() {
+                    return Err(Error::Conflict(format!(
+                        "todo {} changed since `{}`",
+                        change.id, entry.command
+                    )));
+                }
+                todo_list.restore(change.id, to.clone());
+                ids.insert(change.id);
+            }
+            entry.undone = undo;
+        }
+
+        let (present, absent): (Vec<usize>, Vec<usize>) =
+            ids.into_iter().partition(|&id| todo_list.get(id).is_some());
+        if !absent.is_empty() {
+            self.inner.delete(&todo_list, &absent)?;
+        }
+        if !present.is_empty() {
+            self.inner.update(&todo_list, &present)?;
+        }
+        self.journal.save(&entries)?;
+        *self.todos.borrow_mut() = None;
+
+        Ok(indices.into_iter().map(|i| entries[i].clone()).collect())
+    }
+}
+
+impl TodoStore for JournaledStore {
+    fn lock(&self) -> Result<FileLock, Error> {
+        self.inner.lock()
+    }
+
+    fn load(&self) -> Result<TodoList, Error> {
+        let todo_list = self.inner.load()?;
+        self.remember(&todo_list);
+        Ok(todo_list)
+    }
+
+    fn load_or_new(&self) -> Result<TodoList, Error> {
+        let todo_list = self.inner.load_or_new()?;
+        self.remember(&todo_list);
+        Ok(todo_list)
+    }
+
+    fn save(&mut self, todo_list: &TodoList) -> Result<(), Error> {
+        self.journaled(todo_list, None, |x| x.save(todo_list))
+    }
+
+    fn insert(&mut self, todo_list: &TodoList, ids: &[usize]) -> Result<(), Error> {
+        self.journaled(todo_list, Some(ids), |x| x.insert(todo_list, ids))
+    }
+
+    fn update(&mut self, todo_list: &TodoList, ids: &[usize]) -> Result<(), Error> {
+        self.journaled(todo_list, Some(ids), |x| x.update(todo_list, ids))
+    }
+
+    fn delete(&mut self, todo_list: &TodoList, ids: &[usize]) -> Result<(), Error> {
+        self.journaled(todo_list, Some(ids), |x| x.delete(todo_list, ids))
+    }
+
+    fn migrate(&mut self, dry_run: bool) -> Result<Vec<Migration>, Error> {
+        self.inner.migrate(dry_run)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::StoreKind;
+    use crate::todo::{PartialTodo, Status, TodoPatch};
+    use rstest::*;
+    use tempfile::{tempdir, TempDir};
+
+    fn journaled_store(kind: StoreKind, command: &str) -> (TempDir, JournaledStore) {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(kind.default_path());
+        let store = JournaledStore::new(
+            kind.open(path.to_str().unwrap()),
+            Journal::new(journal_path(&path)),
+            command,
+        );
+        (dir, store)
+    }
+
+    fn reopen(dir: &TempDir, kind: StoreKind, command: &str) -> JournaledStore {
+        let path = dir.path().join(kind.default_path());
+        JournaledStore::new(
+            kind.open(path.to_str().unwrap()),
+            Journal::new(journal_path(&path)),
+            command,
+        )
+    }
+
+    fn add(store: &mut JournaledStore, title: &str) -> usize {
+        let mut todo_list = store.load_or_new().unwrap();
+        let id = todo_list.add(&PartialTodo {
+            title: String::from(title),
+            ..Default::default()
+        });
+        store.insert(&todo_list, &[id]).unwrap();
+        id
+    }
+
+    fn titles(store: &JournaledStore) -> Vec<String> {
+        let todo_list = store.load_or_new().unwrap();
+        todo_list.list().iter().map(|x| x.title.clone()).collect()
+    }
+
+    #[rstest]
+    #[case(StoreKind::Json)]
+    #[case(StoreKind::Sqlite)]
+    fn should_undo_and_redo_operations(#[case] kind: StoreKind) {
+        let (dir, mut store) = journaled_store(kind, "add");
+        add(&mut store, "title0");
+        let mut store = reopen(&dir, kind, "add");
+        add(&mut store, "title1");
+
+        let mut store = reopen(&dir, kind, "rm 0");
+        let mut todo_list = store.load().unwrap();
+        todo_list.remove(&[0]);
+        store.delete(&todo_list, &[0]).unwrap();
+        assert_eq!(titles(&store), vec!["title1"]);
+
+        let undone = store.undo(2).unwrap();
+        assert_eq!(
+            undone.iter().map(|x| &x.command[..]).collect::<Vec<_>>(),
+            vec!["rm 0", "add"]
+        );
+        assert_eq!(titles(&store), vec!["title0"]);
+
+        store.redo(1).unwrap();
+        assert_eq!(titles(&store), vec!["title0", "title1"]);
+        assert!(store.redo(2).is_ok());
+        assert_eq!(titles(&store), vec!["title1"]);
+        assert_eq!(store.redo(1).unwrap_err().to_string(), "nothing to redo");
+    }
+
+    #[test]
+    fn should_record_one_entry_per_command() {
+        let (_dir, mut store) = journaled_store(StoreKind::Json, "import");
+        add(&mut store, "title0");
+        add(&mut store, "title1");
+
+        let entries = store.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].changes.len(), 2);
+    }
+
+    #[rstest]
+    #[case(StoreKind::Json)]
+    #[case(StoreKind::Sqlite)]
+    fn should_journal_changes_written_ahead_of_their_ids(#[case] kind: StoreKind) {
+        let (dir, mut store) = journaled_store(kind, "add");
+        add(&mut store, "title0");
+        add(&mut store, "title1");
+
+        let mut store = reopen(&dir, kind, "rm 0 --children reparent");
+        let mut todo_list = store.load().unwrap();
+        let patch = TodoPatch {
+            parent: Some(Some(0)),
+            ..Default::default()
+        };
+        todo_list.update(1, &patch).unwrap();
+        store.update(&todo_list, &[1]).unwrap();
+        let removal = todo_list
+            .remove_with(&[0], crate::todo::ChildPolicy::Reparent)
+            .unwrap();
+        store.delete(&todo_list, &removal.removed).unwrap();
+        store.update(&todo_list, &removal.reparented).unwrap();
+
+        store.undo(1).unwrap();
+        let todo_list = store.load().unwrap();
+        assert_eq!(todo_list.list().len(), 2);
+        assert_eq!(todo_list.get(1).unwrap().parent, None);
+    }
+
+    #[test]
+    fn should_keep_last_entries_within_journal_limit() {
+        let (dir, mut store) = journaled_store(StoreKind::Sqlite, "add 0");
+        for i in 0..JOURNAL_LIMIT + 20 {
+            store = reopen(&dir, StoreKind::Sqlite, &format!("add {}", i));
+            add(&mut store, &format!("title{}", i));
+        }
+
+        let entries = store.entries().unwrap();
+        assert_eq!(entries.len(), JOURNAL_LIMIT);
+        assert_eq!(entries[0].command, "add 20");
+        assert_eq!(entries[JOURNAL_LIMIT - 1].seq, JOURNAL_LIMIT + 20);
+
+        let path = journal_path(&dir.path().join(StoreKind::Sqlite.default_path()));
+        let lines = fs::read_to_string(path).unwrap().lines().count();
+        assert_eq!(lines, JOURNAL_LIMIT + 20);
+    }
+
+    #[test]
+    fn should_drop_undone_entries_on_new_operation() {
+        let (dir, mut store) = journaled_store(StoreKind::Json, "add");
+        add(&mut store, "title0");
+        let mut store = reopen(&dir, StoreKind::Json, "add");
+        add(&mut store, "title1");
+        store.undo(1).unwrap();
+
+        let mut store = reopen(&dir, StoreKind::Json, "done 0");
+        let mut todo_list = store.load().unwrap();
+        let patch = TodoPatch {
+            status: Some(Status::Done),
+            ..Default::default()
+        };
+        todo_list.update(0, &patch).unwrap();
+        store.update(&todo_list, &[0]).unwrap();
+
+        let entries = store.entries().unwrap();
+        assert_eq!(
+            entries.iter().map(|x| &x.command[..]).collect::<Vec<_>>(),
+            vec!["add", "done 0"]
+        );
+        assert_eq!(store.redo(1).unwrap_err().to_string(), "nothing to redo");
+    }
+
+    #[test]
+    fn should_not_undo_if_todo_changed_outside_journal() {
+        let (dir, mut store) = journaled_store(StoreKind::Json, "add");
+        add(&mut store, "title0");
+
+        let path = dir.path().join(StoreKind::Json.default_path());
+        let mut todo_list = TodoList::load(path.to_str().unwrap()).unwrap();
+        let patch = TodoPatch {
+            title: Some(String::from("edited")),
+            ..Default::default()
+        };
+        todo_list.update(0, &patch).unwrap();
+        todo_list.save(path.to_str().unwrap()).unwrap();
+
+        let res = store.undo(1);
+        assert!(matches!(res, Err(Error::Conflict(_))), "res={:?}", res);
+        assert_eq!(titles(&store), vec!["edited"]);
+        assert!(!store.entries().unwrap()[0].undone);
+    }
+}
@@ -1,101 +1,241 @@
+pub mod agenda;
+mod args;
+pub mod config;
+pub mod date;
+mod error;
+pub mod filter;
+pub mod format;
+pub mod journal;
+pub mod lock;
+pub mod migrate;
+pub mod query;
+pub mod reader;
+pub mod recurrence;
+pub mod search;
+pub mod store;
 mod subcmd;
-mod todo;
+pub mod table;
+pub mod todo;
 
 #[cfg(test)]
 mod test_utils;
 
-use serde_json::Error as JSONError;
-use std::cmp::max;
-use std::collections::HashMap;
-use std::error::Error;
-use std::fs::File;
-use std::io::{BufReader, Error as IOError};
-use todo::{Todo, TodoList};
-
-fn elide(s: &str, width: usize) -> String {
-    debug_assert!(width > 3);
-
-    if s.chars().count() > width {
-        s.chars().take(width - 3).collect::<String>() + "..."
-    } else {
-        s.to_string()
-    }
+pub use error::{
+    Error, EXIT_CONFLICT, EXIT_FAILURE, EXIT_IO, EXIT_JSON, EXIT_LOCKED, EXIT_NOT_FOUND,
+    EXIT_SUCCESS, EXIT_USAGE,
+};
+
+use config::{Config, Key};
+use journal::{journal_path, Journal, JournaledStore};
+use std::iter::Peekable;
+use std::path::Path;
+use store::StoreKind;
+
+const USAGE: &str = "\
+usage: todo_list [--file <path>] [--store json|sqlite] <subcommand> [options]
+       todo_list <subcommand> --help
+
+subcommands:
+    add -t <title> [-d <description>] [-l <label>[,<label>...]]... [--due <date>]
+        [-p low|normal|high|urgent] [--parent <id>] [--repeat <rule>]
+    rm <id|hash|from-to>... [--where <query>] [-s <status>] [-l <label>]
+        [--children refuse|cascade|reparent]
+    update|edit <id|hash>|--where <query> [-t <title>] [-d <description>] [-l <label>[,<label>...]]... [-s <status>]
+        [--due <date>|none] [-p low|normal|high|urgent] [--parent <id>|none]
+        [--repeat <rule>|none]
+    start <id|hash>...
+    done <id|hash>...
+    search <word>... [-n <count>] [--wrap]
+    list [--where <query>] [-s <status>] [-l <label>] [--search <regex>] [--since <date>] [--until <date>]
+         [--sort id|title|status|label|due|priority] [--reverse] [--columns <column>,...] [--wrap]
+         [-f|--format table|json|ndjson|csv|tsv|markdown]
+    import <file|-> [-f json|ndjson|csv|tsv|markdown] [--on-conflict renumber|skip|overwrite]
+    export [-f json|ndjson|csv|tsv|markdown] [-o <file>]
+    label add <label> <id|hash>...
+    label rm <label> [<id|hash>...]
+    label rename <old> <new>
+    label list
+    tree [<id|hash>] [--wrap]
+    link <id|hash> <blocker>...
+    unlink <id|hash> <blocker>...
+    ready [--wrap]
+    agenda [--wrap]
+    overdue [--wrap]
+    history <id|hash>
+    migrate [-n|--dry-run]
+    config get <key>
+    config set <key> <value>
+    config list
+    undo [<count>]
+    redo [<count>]
+    log [-n <count>]
+
+config keys are file, store, default_label, date_format (strftime, for tables) and columns.
+each is taken from, first match wins: the --file/--store flags, a TODO_LIST_<KEY> environment
+variable (e.g. TODO_LIST_FILE), the config file ($TODO_LIST_CONFIG, else
+$XDG_CONFIG_HOME/todo_list/config.toml, else ~/.config/todo_list/config.toml), then the default.
+
+dates are YYYY-MM-DD, today, tomorrow, yesterday, +<n>d|w|m or [next] <weekday>
+queries are conditions like status:Done, label:infra, due<2026-11-01 or title~^fix joined by
+AND (or a space), OR, NOT and parentheses; fields are id, parent, status, priority, due,
+created, label, title and description, and operators are : = != < <= > >= and ~ (regex)
+repeat rules are daily, weekly, monthly, every <n> days|weeks|months or <n>d|w|m, counted
+from the due date, or from the day it is done if followed by \"after completion\"";
+
+/// Options given before the subcommand.
+#[derive(Debug, PartialEq, Eq, Default)]
+struct GlobalOptions {
+    file: Option<String>,
+    store: Option<StoreKind>,
+    help: bool,
 }
 
-fn display(todo_list: &TodoList) {
-    let id_width = 5;
-    let title_width = 20;
-    let description_width = 20;
-    let status_width = 10;
-    let label_width = 20;
+fn parse_global_options(
+    args: &mut Peekable<impl Iterator<Item = String>>,
+) -> Result<GlobalOptions, Error> {
+    let mut options = GlobalOptions::default();
 
-    let headers = format!(
-        "{:>id_width$} {:<title_width$} {:<description_width$} {:<status_width$} {:<label_width$}",
-        "id", "title", "description", "status", "label"
-    );
-    let underline = "-".repeat(80);
+    while let Some(option) = args.next_if(|x| x.starts_with('-')) {
+        let mut value = || match args.next() {
+            Some(x) => Ok(x),
+            None => Err(Error::invalid_argument(&option, "option requires a value")),
+        };
+        match &option[..] {
+            "--file" => options.file = Some(value()?),
+            "--store" => {
+                options.store = Some(
+                    value()?
+                        .parse()
+                        .map_err(|x| Error::invalid_argument(&option, x))?,
+                )
+            }
+            "-h" | "--help" => options.help = true,
+            _ => return Err(Error::usage(format!("invalid option; {}", option))),
+        }
+    }
 
-    let fmt_todo = |todo: &Todo| -> String {
-        debug_assert!(
-            id_width + title_width + description_width + status_width + label_width <= 76 + 4
-        );
+    Ok(options)
+}
 
-        format!(
-        "{:>id_width$} {:<title_width$} {:<description_width$} {:<status_width$} {:<label_width$}",
-        todo.id,
-        elide(&todo.title, title_width),
-        elide(&todo.description, description_width),
-        elide(&todo.status.to_string(), status_width),
-        elide(&todo.label, label_width)
-    )
+pub fn run(args: impl Iterator<Item = String>) -> i32 {
+    let mut args = args.peekable();
+    let global = parse_global_options(&mut args);
+    if let Ok(GlobalOptions { help: true, .. }) = global {
+        println!("{}", USAGE);
+        return EXIT_SUCCESS;
+    }
+    let args = args.collect::<Vec<_>>();
+    // Errors in the global options are explained by the global usage, not a subcommand's.
+    let subcmd_usage = global.is_ok().then(|| subcmd::usage(&args)).flatten();
+    let mut config = match Config::load() {
+        Ok(x) => x,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return err.exit_code();
+        }
+    };
+    let parsed = global.and_then(|global| {
+        if let Some(file) = global.file {
+            config.set_flag(Key::File, file);
+        }
+        if let Some(store) = global.store {
+            config.set_flag(Key::Store, store.to_string());
+        }
+        subcmd::parse(args.iter().cloned(), &config)
+    });
+    let options = match parsed {
+        Ok(x) => x,
+        Err(err) => {
+            let usage = subcmd_usage.unwrap_or_else(|| USAGE.to_string());
+            eprintln!("error: {}\n\n{}", err, usage);
+            return err.exit_code();
+        }
     };
 
-    println!(
-        "{}\n{}\n{}",
-        headers,
-        underline,
-        todo_list
-            .list()
-            .iter()
-            .map(fmt_todo)
-            .collect::<Vec<_>>()
-            .join("\n")
+    let file = config.file();
+    let mut store = JournaledStore::new(
+        config.store().open(&file),
+        Journal::new(journal_path(Path::new(&file))),
+        &args.join(" "),
     );
+    match subcmd::exec(options, &mut store) {
+        Ok(()) => EXIT_SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            err.exit_code()
+        }
+    }
 }
 
 #[cfg(test)]
-mod display_tests {
-    use super::display;
-    use crate::todo::{Status, Todo, TodoList};
+mod run_tests {
+    use super::*;
+    use crate::test_utils::{copy_todo_list, split_into_string_iter};
+    use rstest::*;
 
     #[test]
-    fn should_display_todo_list_properly() {
-        let todo_list = TodoList::new(
-            vec![
-                Todo {
-                    id: 0,
-                    title: String::from("title0"),
-                    description: String::from("description0"),
-                    status: Status::ToDo,
-                    label: String::from(""),
-                },
-                Todo {
-                    id: 1,
-                    title: String::from("title1").repeat(10),
-                    description: String::from("description1").repeat(10),
-                    status: Status::InProgress,
-                    label: String::from("label1").repeat(10),
-                },
-                Todo {
-                    id: 2,
-                    title: String::from("title2"),
-                    description: String::from("description2"),
-                    status: Status::Done,
-                    label: String::from("label2"),
-                },
-            ]
-            .into_iter(),
+    fn should_return_usage_exit_code_if_invalid_subcmd_given() {
+        let res = subcmd::parse(split_into_string_iter("invalid"), &Config::default());
+
+        assert_eq!(res.unwrap_err().exit_code(), EXIT_USAGE);
+    }
+
+    #[rstest]
+    #[case(split_into_string_iter("list"), Ok(GlobalOptions::default()))]
+    #[case(
+        split_into_string_iter("--store sqlite list"),
+        Ok(GlobalOptions { store: Some(StoreKind::Sqlite), ..Default::default() })
+    )]
+    #[case(
+        split_into_string_iter("--file /tmp/todo.json --store json list"),
+        Ok(GlobalOptions {
+            file: Some(String::from("/tmp/todo.json")),
+            store: Some(StoreKind::Json),
+            help: false,
+        })
+    )]
+    #[case(
+        split_into_string_iter("--help list"),
+        Ok(GlobalOptions { help: true, ..Default::default() })
+    )]
+    #[case(
+        split_into_string_iter("--store"),
+        Err("--store: option requires a value")
+    )]
+    #[case(
+        split_into_string_iter("--file"),
+        Err("--file: option requires a value")
+    )]
+    #[case(
+        split_into_string_iter("--store csv list"),
+        Err("--store: invalid store; csv")
+    )]
+    #[case(split_into_string_iter("-x list"), Err("invalid option; -x"))]
+    fn should_parse_global_options(
+        #[case] args: impl Iterator<Item = String>,
+        #[case] expected: Result<GlobalOptions, &str>,
+    ) {
+        let mut args = args.peekable();
+
+        let res = parse_global_options(&mut args).map_err(|x| x.to_string());
+        assert_eq!(res, expected.map_err(String::from));
+        if res.is_ok() {
+            assert_eq!(args.next(), Some(String::from("list")));
+        }
+    }
+
+    #[test]
+    fn should_return_not_found_exit_code_if_todo_missing() {
+        let (_dir, path) = copy_todo_list();
+        let mut store = JournaledStore::new(
+            StoreKind::Json.open(&path),
+            Journal::new(journal_path(Path::new(&path))),
+            "done 99",
         );
-        display(&todo_list);
+
+        let options = subcmd::parse(split_into_string_iter("done 99"), &Config::default()).unwrap();
+        let res = subcmd::exec(options, &mut store);
+
+        assert_eq!(res.unwrap_err().exit_code(), EXIT_NOT_FOUND);
     }
 }
@@ -0,0 +1,138 @@
+use crate::Error;
+use chrono::Local;
+use std::env;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const RETRY_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Exclusive advisory lock on `<store>.lock`, held until dropped.
+///
+/// The lock file records who holds it so a process that times out can say who to wait for.
+/// It is left in place on release: removing it could let a waiter lock a file that another
+/// process is about to replace.
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+}
+
+/// The lock file guarding the store at `path`.
+pub fn lock_path(path: &Path) -> PathBuf {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    PathBuf::from(lock_path)
+}
+
+fn holder() -> String {
+    let user = env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| String::from("unknown"));
+    format!(
+        "pid {} (user {}) since {}",
+        std::process::id(),
+        user,
+        Local::now().format("%Y-%m-%d %H:%M:%S")
+    )
+}
+
+impl FileLock {
+    /// Waits up to `timeout` for the lock on `path`.
+    pub fn acquire(path: &Path, timeout: Duration) -> Result<Self, Error> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(Error::io(path))?;
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            match file.try_lock() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                    thread::sleep(RETRY_INTERVAL)
+                }
+                Err(TryLockError::WouldBlock) => {
+                    let mut holder = String::new();
+                    file.read_to_string(&mut holder).map_err(Error::io(path))?;
+                    let holder = match holder.trim() {
+                        "" => "another process",
+                        x => x,
+                    };
+                    return Err(Error::Locked(format!(
+                        "{} is locked by {}; gave up after {}s",
+                        path.display(),
+                        holder,
+                        timeout.as_secs_f32()
+                    )));
+                }
+                Err(TryLockError::Error(err)) => return Err(Error::io(path)(err)),
+            }
+        }
+
+        file.set_len(0)
+            .and_then(|()| file.rewind())
+            .and_then(|()| writeln!(file, "{}", holder()))
+            .and_then(|()| file.flush())
+            .map_err(Error::io(path))?;
+
+        Ok(FileLock { file })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn should_name_holder_when_lock_times_out() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("todo-list.json.lock");
+        let _lock = FileLock::acquire(&path, Duration::ZERO).unwrap();
+
+        let res = FileLock::acquire(&path, Duration::from_millis(50));
+
+        assert!(res.is_err(), "not err; res={:?}", res);
+        let re = Regex::new(&format!(
+            r"is locked by pid {} \(user .+\) since .+; gave up after 0.05s$",
+            std::process::id()
+        ))
+        .unwrap();
+        let message = res.unwrap_err().to_string();
+        assert!(re.is_match(&message), "message={message}");
+    }
+
+    #[test]
+    fn should_release_lock_on_drop() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("todo-list.json.lock");
+
+        drop(FileLock::acquire(&path, Duration::ZERO).unwrap());
+        assert!(fs::read_to_string(&path).unwrap().is_empty());
+
+        assert!(FileLock::acquire(&path, Duration::ZERO).is_ok());
+    }
+
+    #[test]
+    fn should_append_lock_extension() {
+        assert_eq!(
+            lock_path(Path::new("dir/todo-list.json")),
+            PathBuf::from("dir/todo-list.json.lock")
+        );
+    }
+}
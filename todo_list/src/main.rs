@@ -1 +1,7 @@
-fn main() {}
+use std::env;
+use std::process;
+use todo_list::run;
+
+fn main() {
+    process::exit(run(env::args().skip(1)));
+}
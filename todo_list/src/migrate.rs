@@ -0,0 +1,187 @@
+use crate::todo::SCHEMA_VERSION;
+use crate::Error;
+use serde_json::{json, Value};
+
+/// One applied upgrade step and a human-readable description of each change it made.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Migration {
+    pub from: u32,
+    pub to: u32,
+    pub changes: Vec<String>,
+}
+
+type Step = fn(&mut Value) -> Result<Vec<String>, Error>;
+
+/// `STEPS[n]` upgrades a version `n` file to version `n + 1`.
+const STEPS: [Step; SCHEMA_VERSION as usize] = [wrap_in_envelope, split_label_field];
+
+/// Version of a stored file: 0 for the bare array of todos, otherwise its `version` field.
+pub fn version_of(value: &Value) -> Result<u32, Error> {
+    match value {
+        Value::Array(_) => Ok(0),
+        Value::Object(x) => x
+            .get("version")
+            .and_then(Value::as_u64)
+            .and_then(|x| u32::try_from(x).ok())
+            .ok_or_else(|| Error::Schema(String::from("file version missing"))),
+        _ => Err(Error::Schema(String::from(
+            "invalid file; expected an array or an object",
+        ))),
+    }
+}
+
+/// Upgrades `value` in place to [`SCHEMA_VERSION`], returning the steps applied in order.
+pub fn migrate(value: &mut Value) -> Result<Vec<Migration>, Error> {
+    let version = version_of(value)?;
+    if version > SCHEMA_VERSION {
+        return Err(Error::Schema(format!(
+            "unsupported file version; {}",
+            version
+        )));
+    }
+
+    (version..SCHEMA_VERSION)
+        .map(|from| {
+            let changes = STEPS[from as usize](value)?;
+            value["version"] = json!(from + 1);
+            Ok(Migration {
+                from,
+                to: from + 1,
+                changes,
+            })
+        })
+        .collect()
+}
+
+fn todos_mut(value: &mut Value) -> Result<&mut Vec<Value>, Error> {
+    value
+        .get_mut("todos")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| Error::Schema(String::from("invalid file; todos array missing")))
+}
+
+/// 0 -> 1: wraps the bare array in `{ "version", "next_id", "todos" }`.
+fn wrap_in_envelope(value: &mut Value) -> Result<Vec<String>, Error> {
+    let todos = value.take();
+    let next_id = todos
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|x| x.get("id").and_then(Value::as_u64))
+        .map(|x| x + 1)
+        .max()
+        .unwrap_or(0);
+    let count = todos.as_array().map_or(0, Vec::len);
+
+    *value = json!({ "version": 1, "next_id": next_id, "todos": todos });
+
+    Ok(vec![format!(
+        "wrapped {} todo(s) in a versioned envelope with next_id={}",
+        count, next_id
+    )])
+}
+
+/// 1 -> 2: replaces the single `label` string with a `labels` array.
+fn split_label_field(value: &mut Value) -> Result<Vec<String>, Error> {
+    let mut count = 0;
+    for todo in todos_mut(value)?
+        .iter_mut()
+        .filter_map(Value::as_object_mut)
+    {
+        if let Some(label) = todo.remove("label") {
+            let labels = match label {
+                Value::String(x) if x.is_empty() => json!([]),
+                Value::String(x) => json!([x]),
+                x => x,
+            };
+            todo.entry("labels").or_insert(labels);
+            count += 1;
+        }
+    }
+
+    Ok(if count == 0 {
+        Vec::new()
+    } else {
+        vec![format!("converted label to labels on {} todo(s)", count)]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case(json!([]), Ok(0))]
+    #[case(json!({"version": 1, "next_id": 0, "todos": []}), Ok(1))]
+    #[case(json!({"todos": []}), Err(String::from("file version missing")))]
+    #[case(json!("x"), Err(String::from("invalid file; expected an array or an object")))]
+    fn should_detect_version(#[case] value: Value, #[case] expected: Result<u32, String>) {
+        assert_eq!(version_of(&value).map_err(|x| x.to_string()), expected);
+    }
+
+    #[test]
+    fn should_migrate_bare_array_to_current_version() {
+        let mut value = json!([
+            {"id": 0, "title": "t0", "description": "", "status": "ToDo", "label": ""},
+            {"id": 4, "title": "t4", "description": "", "status": "Done", "label": "a"},
+        ]);
+
+        let migrations = migrate(&mut value).unwrap();
+
+        assert_eq!(
+            migrations,
+            vec![
+                Migration {
+                    from: 0,
+                    to: 1,
+                    changes: vec![String::from(
+                        "wrapped 2 todo(s) in a versioned envelope with next_id=5"
+                    )],
+                },
+                Migration {
+                    from: 1,
+                    to: 2,
+                    changes: vec![String::from("converted label to labels on 2 todo(s)")],
+                },
+            ]
+        );
+        assert_eq!(
+            value,
+            json!({
+                "version": 2,
+                "next_id": 5,
+                "todos": [
+                    {"id": 0, "title": "t0", "description": "", "status": "ToDo", "labels": []},
+                    {"id": 4, "title": "t4", "description": "", "status": "Done", "labels": ["a"]},
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn should_not_change_current_version() {
+        let mut value = json!({"version": SCHEMA_VERSION, "next_id": 0, "todos": []});
+        let expected = value.clone();
+
+        assert_eq!(migrate(&mut value).unwrap(), Vec::new());
+        assert_eq!(value, expected);
+    }
+
+    #[rstest]
+    #[case(json!({"version": 99, "todos": []}), "unsupported file version; 99")]
+    #[case(json!({"version": 1, "next_id": 0}), "invalid file; todos array missing")]
+    fn should_return_error_if_file_cannot_be_migrated(
+        #[case] value: Value,
+        #[case] expected: &str,
+    ) {
+        let mut value = value;
+
+        let res = migrate(&mut value);
+        assert!(
+            matches!(&res, Err(Error::Schema(x)) if x == expected),
+            "res={:?}",
+            res
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use crate::date::local_date;
+use crate::filter::Filter;
+use crate::todo::{Status, Todo, TodoList};
+use chrono::NaiveDate;
+use regex::Regex;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Id,
+    Title,
+    Status,
+    Label,
+    Due,
+    Priority,
+}
+
+impl SortKey {
+    fn compare(&self, a: &Todo, b: &Todo) -> Ordering {
+        match self {
+            Self::Id => a.id.cmp(&b.id),
+            Self::Title => a.title.cmp(&b.title),
+            Self::Status => a.status.cmp(&b.status),
+            Self::Label => a.labels.cmp(&b.labels),
+            Self::Due => (a.due.is_none(), a.due).cmp(&(b.due.is_none(), b.due)),
+            Self::Priority => b.priority.cmp(&a.priority),
+        }
+        .then(a.id.cmp(&b.id))
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "id" => Ok(Self::Id),
+            "title" => Ok(Self::Title),
+            "status" => Ok(Self::Status),
+            "label" => Ok(Self::Label),
+            "due" => Ok(Self::Due),
+            "priority" => Ok(Self::Priority),
+            _ => Err(format!("invalid sort key; {}", s)),
+        }
+    }
+}
+
+/// Filters and ordering applied by [`TodoList::query`].
+/// `label` keeps todos carrying that label; `search` is matched against title,
+/// description and each label. `since` and `until` keep todos created on or between those
+/// local days; todos without a creation time never match them. `filter` is a query
+/// such as `status:Done label:infra`.
+#[derive(Debug, Default)]
+pub struct Query {
+    pub filter: Option<Filter>,
+    pub status: Option<Status>,
+    pub label: Option<String>,
+    pub search: Option<Regex>,
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    pub sort: Option<SortKey>,
+    pub reverse: bool,
+}
+
+impl PartialEq for Query {
+    fn eq(&self, other: &Self) -> bool {
+        self.filter == other.filter
+            && self.status == other.status
+            && self.label == other.label
+            && self.search.as_ref().map(|x| x.as_str()) == other.search.as_ref().map(|x| x.as_str())
+            && self.since == other.since
+            && self.until == other.until
+            && self.sort == other.sort
+            && self.reverse == other.reverse
+    }
+}
+
+impl Eq for Query {}
+
+impl Query {
+    pub fn matches(&self, todo: &Todo) -> bool {
+        self.filter.as_ref().is_none_or(|x| x.matches(todo))
+            && self.status.is_none_or(|x| todo.status == x)
+            && self.label.as_ref().is_none_or(|x| todo.labels.contains(x))
+            && self.search.as_ref().is_none_or(|re| {
+                re.is_match(&todo.title)
+                    || re.is_match(&todo.description)
+                    || todo.labels.iter().any(|x| re.is_match(x))
+            })
+            && self.since.is_none_or(|x| {
+                todo.created_at
+                    .is_some_and(|created_at| local_date(created_at) >= x)
+            })
+            && self.until.is_none_or(|x| {
+                todo.created_at
+                    .is_some_and(|created_at| local_date(created_at) <= x)
+            })
+    }
+}
+
+impl TodoList {
+    pub fn query<'a>(&'a self, query: &Query) -> impl Iterator<Item = &'a Todo> {
+        let mut todos = self
+            .list()
+            .iter()
+            .filter(|x| query.matches(x))
+            .collect::<Vec<_>>();
+
+        if let Some(key) = query.sort {
+            todos.sort_by(|a, b| key.compare(a, b));
+        }
+        if query.reverse {
+            todos.reverse();
+        }
+
+        todos.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::{PartialTodo, Priority, TodoPatch};
+    use chrono::{Local, TimeZone, Utc};
+    use rstest::*;
+
+    fn ids(todo_list: &TodoList, query: &Query) -> Vec<usize> {
+        todo_list.query(query).map(|x| x.id).collect()
+    }
+
+    #[rstest]
+    #[case(Query::default(), vec![0, 1, 2])]
+    #[case(Query { status: Some(Status::Done), ..Default::default() }, vec![2])]
+    #[case(Query { label: Some(String::from("label1")), ..Default::default() }, vec![1])]
+    #[case(Query { search: Some(Regex::new(r"^title[02]$").unwrap()), ..Default::default() }, vec![0, 2])]
+    #[case(Query { search: Some(Regex::new(r"label").unwrap()), ..Default::default() }, vec![1, 2])]
+    #[case(Query { reverse: true, ..Default::default() }, vec![2, 1, 0])]
+    #[case(
+        Query { sort: Some(SortKey::Label), reverse: true, ..Default::default() },
+        vec![2, 1, 0]
+    )]
+    #[case(
+        Query { status: Some(Status::ToDo), search: Some(Regex::new("1").unwrap()), ..Default::default() },
+        vec![]
+    )]
+    fn should_query_todos(#[case] query: Query, #[case] expected: Vec<usize>) {
+        let todo_list = TodoList::load("test_assets/todo-list.json").unwrap();
+
+        assert_eq!(ids(&todo_list, &query), expected);
+    }
+
+    #[test]
+    fn should_sort_stably_by_id_within_same_key() {
+        let mut todo_list = TodoList::load("test_assets/todo-list.json").unwrap();
+        todo_list
+            .update(
+                0,
+                &TodoPatch {
+                    status: Some(Status::Done),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let query = Query {
+            sort: Some(SortKey::Status),
+            ..Default::default()
+        };
+
+        assert_eq!(ids(&todo_list, &query), vec![1, 0, 2]);
+    }
+
+    #[rstest]
+    #[case(SortKey::Due, vec![2, 0, 1])]
+    #[case(SortKey::Priority, vec![1, 0, 2])]
+    fn should_sort_by_due_and_priority(#[case] sort: SortKey, #[case] expected: Vec<usize>) {
+        let mut todo_list = TodoList::load("test_assets/todo-list.json").unwrap();
+        for (id, due, priority) in [
+            (0, NaiveDate::from_ymd_opt(2026, 11, 2), Priority::Normal),
+            (1, None, Priority::Urgent),
+            (2, NaiveDate::from_ymd_opt(2026, 11, 1), Priority::Low),
+        ] {
+            todo_list
+                .update(
+                    id,
+                    &TodoPatch {
+                        due: Some(due),
+                        priority: Some(priority),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+        }
+
+        let query = Query {
+            sort: Some(sort),
+            ..Default::default()
+        };
+
+        assert_eq!(ids(&todo_list, &query), expected);
+    }
+
+    #[rstest]
+    #[case(Some(14), None, vec![4, 5])]
+    #[case(None, Some(14), vec![3, 4])]
+    #[case(Some(14), Some(14), vec![4])]
+    fn should_filter_by_creation_day(
+        #[case] since: Option<u32>,
+        #[case] until: Option<u32>,
+        #[case] expected: Vec<usize>,
+    ) {
+        let mut todo_list = TodoList::load("test_assets/todo-list.json").unwrap();
+        for day in [10, 14, 18] {
+            let created_at = Local.with_ymd_and_hms(2026, 10, day, 23, 30, 0).unwrap();
+            todo_list.add_at(
+                &PartialTodo {
+                    title: format!("day{}", day),
+                    ..Default::default()
+                },
+                created_at.with_timezone(&Utc),
+            );
+        }
+        todo_list.remove(&[0, 1, 2]);
+        let date = |x: Option<u32>| x.and_then(|day| NaiveDate::from_ymd_opt(2026, 10, day));
+
+        let query = Query {
+            since: date(since),
+            until: date(until),
+            ..Default::default()
+        };
+
+        assert_eq!(ids(&todo_list, &query), expected);
+    }
+
+    #[rstest]
+    #[case("id", Ok(SortKey::Id))]
+    #[case("title", Ok(SortKey::Title))]
+    #[case("status", Ok(SortKey::Status))]
+    #[case("label", Ok(SortKey::Label))]
+    #[case("due", Ok(SortKey::Due))]
+    #[case("priority", Ok(SortKey::Priority))]
+    #[case("size", Err(String::from("invalid sort key; size")))]
+    fn should_parse_sort_key(#[case] s: &str, #[case] expected: Result<SortKey, String>) {
+        assert_eq!(s.parse::<SortKey>(), expected);
+    }
+}
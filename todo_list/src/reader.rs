@@ -0,0 +1,398 @@
+use crate::date::DATE_FORMAT;
+use crate::format::Format;
+use crate::todo::{split_labels, Labels, Priority, Status, Todo};
+use crate::Error;
+use chrono::NaiveDate;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::path::Path;
+
+lazy_static! {
+    static ref HEADING: Regex = Regex::new(r"^(#+)\s+(.*?)\s*#*\s*$").unwrap();
+    static ref CHECKBOX: Regex = Regex::new(r"^\s*[-*+]\s+\[([ xX])\]\s+(.*?)\s*$").unwrap();
+}
+
+/// Todos read from an import source. `keyed` todos carry ids worth keeping;
+/// `unkeyed` ones (Markdown items, CSV rows with a blank id) need new ids.
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct Records {
+    pub keyed: Vec<Todo>,
+    pub unkeyed: Vec<Todo>,
+}
+
+fn new_todo(title: String, description: String, status: Status, labels: Labels) -> Todo {
+    Todo {
+        id: 0,
+        title,
+        description,
+        status,
+        labels,
+        ..Default::default()
+    }
+}
+
+pub fn read_json(input: &str) -> Result<Records, Error> {
+    Ok(Records {
+        keyed: serde_json::from_str(input)?,
+        unkeyed: Vec::new(),
+    })
+}
+
+pub fn read_ndjson(input: &str) -> Result<Records, Error> {
+    Ok(Records {
+        keyed: input
+            .lines()
+            .filter(|x| !x.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?,
+        unkeyed: Vec::new(),
+    })
+}
+
+/// Reads a CSV/TSV table whose header names columns as `list` does. Only `title` is required;
+/// `labels` (or `label`) holds comma-separated labels and `due` is `YYYY-MM-DD`.
+pub fn read_delimited(input: &str, delimiter: u8) -> Result<Records, Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_reader(input.as_bytes());
+    let headers = reader
+        .headers()?
+        .iter()
+        .map(|x| x.trim().to_lowercase())
+        .collect::<Vec<_>>();
+    let position = |name: &str| headers.iter().position(|x| x == name);
+    let title_index = match position("title") {
+        Some(x) => x,
+        None => {
+            return Err(Error::Record {
+                line: 1,
+                message: String::from("title column required"),
+            })
+        }
+    };
+
+    let mut records = Records::default();
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map_or(0, |x| x.line() as usize);
+        let invalid = |message: String| Error::Record { line, message };
+        let field = |name: &str| {
+            position(name)
+                .and_then(|i| record.get(i))
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let status = match &field("status")[..] {
+            "" => Status::ToDo,
+            x => x.parse().map_err(invalid)?,
+        };
+        let mut todo = new_todo(
+            record.get(title_index).unwrap_or_default().to_string(),
+            field("description"),
+            status,
+            split_labels(&(field("labels") + "," + &field("label"))).collect(),
+        );
+        todo.due = match field("due").trim() {
+            "" => None,
+            x => Some(
+                NaiveDate::parse_from_str(x, DATE_FORMAT)
+                    .map_err(|_| invalid(format!("invalid due date; {}", x)))?,
+            ),
+        };
+        todo.priority = match field("priority").trim() {
+            "" => Priority::default(),
+            x => x.parse().map_err(invalid)?,
+        };
+        todo.parent = match field("parent").trim() {
+            "" => None,
+            x => Some(
+                x.parse()
+                    .map_err(|_| invalid(format!("invalid parent; {}", x)))?,
+            ),
+        };
+        todo.recurrence = match field("recurrence").trim() {
+            "" => None,
+            x => Some(x.parse().map_err(invalid)?),
+        };
+        todo.blocked_by = field("blocked_by")
+            .split(',')
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+            .map(|x| {
+                x.parse()
+                    .map_err(|_| invalid(format!("invalid blocked_by; {}", x)))
+            })
+            .collect::<Result<_, _>>()?;
+        match field("id").trim() {
+            "" => records.unkeyed.push(todo),
+            x => {
+                todo.id = x
+                    .parse()
+                    .map_err(|_| invalid(format!("invalid id; {}", x)))?;
+                records.keyed.push(todo);
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+/// Reads `- [ ] title` / `- [x] title` items; every enclosing heading becomes a label
+/// and indented lines under an item become its description.
+pub fn read_markdown(input: &str) -> Result<Records, Error> {
+    let mut todos: Vec<Todo> = Vec::new();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut in_item = false;
+
+    for line in input.lines() {
+        if let Some(caps) = CHECKBOX.captures(line) {
+            let status = if &caps[1] == " " {
+                Status::ToDo
+            } else {
+                Status::Done
+            };
+            todos.push(new_todo(
+                caps[2].to_string(),
+                String::new(),
+                status,
+                headings.iter().map(|(_, x)| x.clone()).collect(),
+            ));
+            in_item = true;
+        } else if let Some(caps) = HEADING.captures(line) {
+            let level = caps[1].len();
+            while headings.last().is_some_and(|(x, _)| *x >= level) {
+                headings.pop();
+            }
+            headings.push((level, caps[2].to_string()));
+            in_item = false;
+        } else if in_item && line.starts_with([' ', '\t']) && !line.trim().is_empty() {
+            let todo = todos.last_mut().unwrap();
+            if !todo.description.is_empty() {
+                todo.description.push('\n');
+            }
+            todo.description.push_str(line.trim());
+        } else if !line.trim().is_empty() {
+            in_item = false;
+        }
+    }
+
+    Ok(Records {
+        keyed: Vec::new(),
+        unkeyed: todos,
+    })
+}
+
+pub fn read(format: Format, input: &str) -> Result<Records, Error> {
+    match format {
+        Format::Json => read_json(input),
+        Format::Ndjson => read_ndjson(input),
+        Format::Csv => read_delimited(input, b','),
+        Format::Tsv => read_delimited(input, b'\t'),
+        Format::Markdown => read_markdown(input),
+        Format::Table => Err(Error::Invalid(String::from(
+            "table format cannot be imported",
+        ))),
+    }
+}
+
+/// Guesses the format from a file extension.
+pub fn infer_format(file_path: &str) -> Option<Format> {
+    let extension = Path::new(file_path).extension()?.to_str()?;
+    match extension.to_lowercase().as_str() {
+        "json" => Some(Format::Json),
+        "ndjson" | "jsonl" => Some(Format::Ndjson),
+        "csv" => Some(Format::Csv),
+        "tsv" => Some(Format::Tsv),
+        "md" | "markdown" => Some(Format::Markdown),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table::Column;
+    use crate::todo::TodoList;
+    use rstest::*;
+
+    fn titles(todos: &[Todo]) -> Vec<(&str, Status, Vec<&str>)> {
+        todos
+            .iter()
+            .map(|x| {
+                (
+                    &x.title[..],
+                    x.status,
+                    x.labels.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_read_markdown_checklist() {
+        let input = "\
+# Project
+
+intro paragraph
+- [ ] write docs
+  with examples
+  and tests
+- [x] ship it
+
+## infra ##
+
+* [X] rotate keys
+  - [ ] nested item
+- not a todo
+  not a description
+
+# Other
+- [ ] release
+";
+
+        let records = read_markdown(input).unwrap();
+
+        assert!(records.keyed.is_empty());
+        assert_eq!(
+            titles(&records.unkeyed),
+            vec![
+                ("write docs", Status::ToDo, vec!["Project"]),
+                ("ship it", Status::Done, vec!["Project"]),
+                ("rotate keys", Status::Done, vec!["Project", "infra"]),
+                ("nested item", Status::ToDo, vec!["Project", "infra"]),
+                ("release", Status::ToDo, vec!["Other"]),
+            ]
+        );
+        assert_eq!(records.unkeyed[0].description, "with examples\nand tests");
+        assert_eq!(records.unkeyed[3].description, "");
+    }
+
+    #[test]
+    fn should_read_csv_with_and_without_ids() {
+        let input = "Title,id,status,description,labels\n\
+                     title0,3,Done,\"a, b\nc\",\n\
+                     title1,,,,\"b, a\"\n";
+
+        let records = read_delimited(input, b',').unwrap();
+
+        assert_eq!(
+            records.keyed,
+            vec![new_todo(
+                String::from("title0"),
+                String::from("a, b\nc"),
+                Status::Done,
+                Labels::new()
+            )]
+            .into_iter()
+            .map(|mut x| {
+                x.id = 3;
+                x
+            })
+            .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            titles(&records.unkeyed),
+            vec![("title1", Status::ToDo, vec!["a", "b"])]
+        );
+    }
+
+    #[test]
+    fn should_read_due_and_priority_from_csv() {
+        let input = "title,due,priority\n\
+                     title0,2026-11-01,urgent\n\
+                     title1,,\n";
+
+        let records = read_delimited(input, b',').unwrap();
+
+        assert_eq!(
+            records
+                .unkeyed
+                .iter()
+                .map(|x| (x.due, x.priority))
+                .collect::<Vec<_>>(),
+            vec![
+                (NaiveDate::from_ymd_opt(2026, 11, 1), Priority::Urgent),
+                (None, Priority::Normal),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case("id,description\n0,x\n", r"line 1: title column required")]
+    #[case("id,title\nx,title0\n", r"line 2: invalid id; x")]
+    #[case("title,status\ntitle0,Doing\n", r"line 2: invalid status; Doing")]
+    #[case(
+        "title,due\ntitle0,\ntitle1,tomorrow\n",
+        r"line 3: invalid due date; tomorrow"
+    )]
+    #[case("title,priority\ntitle0,asap\n", r"line 2: invalid priority; asap")]
+    #[case(
+        "title,recurrence\ntitle0,yearly\n",
+        r"line 2: invalid recurrence; yearly"
+    )]
+    #[case("title,parent\ntitle0,x\n", r"line 2: invalid parent; x")]
+    #[case(
+        "title,description,blocked_by\ntitle0,\"two\nlines\",\"1, x\"\n",
+        r"line 2: invalid blocked_by; x"
+    )]
+    fn should_return_error_if_csv_is_invalid(#[case] input: &str, #[case] expected: &str) {
+        let res = read_delimited(input, b',');
+
+        assert!(res.is_err(), "not err; res={:?}", res);
+        let err = res.unwrap_err();
+        assert_eq!(err.to_string(), expected);
+        assert_eq!(err.exit_code(), crate::error::EXIT_JSON);
+    }
+
+    #[rstest]
+    #[case(Format::Json)]
+    #[case(Format::Ndjson)]
+    #[case(Format::Csv)]
+    #[case(Format::Tsv)]
+    fn should_round_trip_with_formatter(#[case] format: Format) {
+        let todo_list = TodoList::load("test_assets/todo-list.json").unwrap();
+        let todos = todo_list.list().iter().collect::<Vec<_>>();
+        let mut out = Vec::new();
+        format
+            .formatter(false, DATE_FORMAT)
+            .write(&todos, &Column::DEFAULT, &mut out)
+            .unwrap();
+
+        let records = read(format, &String::from_utf8(out).unwrap()).unwrap();
+
+        assert_eq!(TodoList::new(records.keyed.into_iter()), todo_list);
+    }
+
+    #[test]
+    fn should_round_trip_markdown_except_ids_and_in_progress() {
+        let todo_list = TodoList::load("test_assets/todo-list.json").unwrap();
+        let todos = todo_list.list().iter().collect::<Vec<_>>();
+        let mut out = Vec::new();
+        Format::Markdown
+            .formatter(false, DATE_FORMAT)
+            .write(&todos, &Column::DEFAULT, &mut out)
+            .unwrap();
+
+        let records = read(Format::Markdown, &String::from_utf8(out).unwrap()).unwrap();
+
+        assert_eq!(
+            titles(&records.unkeyed),
+            vec![
+                ("title0", Status::ToDo, vec![]),
+                ("title1", Status::ToDo, vec!["label1"]),
+                ("title2", Status::Done, vec!["label2"]),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case("todos.json", Some(Format::Json))]
+    #[case("todos.jsonl", Some(Format::Ndjson))]
+    #[case("dir.d/todos.CSV", Some(Format::Csv))]
+    #[case("todos.md", Some(Format::Markdown))]
+    #[case("dir.d/todos", None)]
+    fn should_infer_format(#[case] file_path: &str, #[case] expected: Option<Format>) {
+        assert_eq!(infer_format(file_path), expected);
+    }
+}
@@ -0,0 +1,215 @@
+use chrono::{Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+const AFTER_COMPLETION: &str = " after completion";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    Days(u32),
+    Weeks(u32),
+    Months(u32),
+}
+
+impl Interval {
+    /// The date one interval after `date`; months past the end of a shorter month clamp
+    /// to its last day.
+    pub fn after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match *self {
+            Self::Days(n) => date.checked_add_days(Days::new(n.into())),
+            Self::Weeks(n) => date.checked_add_days(Days::new(7 * u64::from(n))),
+            Self::Months(n) => date.checked_add_months(Months::new(n)),
+        }
+    }
+}
+
+/// How a todo repeats. Written as `daily`, `weekly`, `monthly`, `every <n> days|weeks|months`
+/// or `<n>d|w|m`, optionally followed by `after completion`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Recurrence {
+    pub interval: Interval,
+    /// Counts the interval from the day the todo was done rather than from its due date.
+    pub after_completion: bool,
+}
+
+impl Recurrence {
+    /// Due date of the occurrence following one due on `due` and done on `done`.
+    /// A fixed schedule skips occurrences that would already have been due by `done`,
+    /// so a chore done late is not followed by one that is overdue from the start.
+    pub fn next(&self, due: Option<NaiveDate>, done: NaiveDate) -> Option<NaiveDate> {
+        let mut next = match due.filter(|_| !self.after_completion) {
+            Some(due) => self.interval.after(due)?,
+            None => return self.interval.after(done),
+        };
+        while next <= done {
+            next = self.interval.after(next)?;
+        }
+
+        Some(next)
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let interval = match self.interval {
+            Interval::Days(1) => String::from("daily"),
+            Interval::Weeks(1) => String::from("weekly"),
+            Interval::Months(1) => String::from("monthly"),
+            Interval::Days(n) => format!("every {} days", n),
+            Interval::Weeks(n) => format!("every {} weeks", n),
+            Interval::Months(n) => format!("every {} months", n),
+        };
+        let suffix = if self.after_completion {
+            AFTER_COMPLETION
+        } else {
+            ""
+        };
+        write!(f, "{}{}", interval, suffix)
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid recurrence; {}", s);
+        let lower = s.trim().to_lowercase();
+        let (rule, after_completion) = match lower.strip_suffix(AFTER_COMPLETION) {
+            Some(x) => (x.trim_end(), true),
+            None => (&lower[..], false),
+        };
+
+        let (n, unit) = match rule {
+            "daily" => ("1", "d"),
+            "weekly" => ("1", "w"),
+            "monthly" => ("1", "m"),
+            _ => match rule.strip_prefix("every ") {
+                Some(rest) => match rest.split_once(' ') {
+                    Some((n, unit)) => (n, unit),
+                    None => ("1", rest),
+                },
+                None => rule.split_at(rule.char_indices().last().map_or(0, |(i, _)| i)),
+            },
+        };
+        let n = n
+            .parse::<u32>()
+            .ok()
+            .filter(|&x| x > 0)
+            .ok_or_else(invalid)?;
+        let interval = match unit {
+            "d" | "day" | "days" => Interval::Days(n),
+            "w" | "week" | "weeks" => Interval::Weeks(n),
+            "m" | "month" | "months" => Interval::Months(n),
+            _ => return Err(invalid()),
+        };
+
+        Ok(Self {
+            interval,
+            after_completion,
+        })
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(recurrence: Recurrence) -> Self {
+        recurrence.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::DATE_FORMAT;
+    use rstest::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, DATE_FORMAT).unwrap()
+    }
+
+    #[rstest]
+    #[case("daily", Interval::Days(1), false, "daily")]
+    #[case("Weekly", Interval::Weeks(1), false, "weekly")]
+    #[case("3d", Interval::Days(3), false, "every 3 days")]
+    #[case("every 2 weeks", Interval::Weeks(2), false, "every 2 weeks")]
+    #[case("every month", Interval::Months(1), false, "monthly")]
+    #[case(
+        "monthly after completion",
+        Interval::Months(1),
+        true,
+        "monthly after completion"
+    )]
+    #[case(
+        "10d after completion",
+        Interval::Days(10),
+        true,
+        "every 10 days after completion"
+    )]
+    fn should_parse_and_display_recurrence(
+        #[case] s: &str,
+        #[case] interval: Interval,
+        #[case] after_completion: bool,
+        #[case] expected: &str,
+    ) {
+        let recurrence = s.parse::<Recurrence>().unwrap();
+
+        assert_eq!(
+            recurrence,
+            Recurrence {
+                interval,
+                after_completion
+            }
+        );
+        assert_eq!(recurrence.to_string(), expected);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("yearly")]
+    #[case("0d")]
+    #[case("every")]
+    #[case("every x days")]
+    #[case("after completion")]
+    #[case("日")]
+    fn should_return_error_if_recurrence_is_invalid(#[case] s: &str) {
+        assert_eq!(
+            s.parse::<Recurrence>(),
+            Err(format!("invalid recurrence; {}", s))
+        );
+    }
+
+    #[rstest]
+    #[case("weekly", Some("2026-10-16"), "2026-10-18", "2026-10-23")]
+    #[case("weekly", Some("2026-10-02"), "2026-10-18", "2026-10-23")]
+    #[case("weekly", Some("2026-10-11"), "2026-10-18", "2026-10-25")]
+    #[case(
+        "weekly after completion",
+        Some("2026-10-16"),
+        "2026-10-18",
+        "2026-10-25"
+    )]
+    #[case("3d", None, "2026-10-18", "2026-10-21")]
+    #[case("monthly", Some("2026-01-31"), "2026-01-30", "2026-02-28")]
+    fn should_compute_next_due_date(
+        #[case] recurrence: &str,
+        #[case] due: Option<&str>,
+        #[case] done: &str,
+        #[case] expected: &str,
+    ) {
+        let recurrence = recurrence.parse::<Recurrence>().unwrap();
+
+        assert_eq!(
+            recurrence.next(due.map(date), date(done)),
+            Some(date(expected))
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use crate::todo::Todo;
+use std::collections::BTreeMap;
+
+/// Share of the score a query word keeps when it is only a prefix of the indexed word.
+const PREFIX_FACTOR: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Field {
+    Title,
+    Labels,
+    Description,
+}
+
+impl Field {
+    /// How much an occurrence in this field counts towards a todo's score.
+    fn weight(&self) -> f64 {
+        match self {
+            Self::Title => 3.0,
+            Self::Labels => 2.0,
+            Self::Description => 1.0,
+        }
+    }
+}
+
+/// Splits `s` into lowercase words, treating anything but letters and digits as a separator.
+pub fn tokenize(s: &str) -> impl Iterator<Item = String> + '_ {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_lowercase())
+}
+
+/// A todo matching a search, with its relevance; higher is better.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit<'a> {
+    pub todo: &'a Todo,
+    pub score: f64,
+}
+
+/// Maps each word of the todos' titles, descriptions and labels to where it occurs.
+#[derive(Debug, Default)]
+pub struct Index<'a> {
+    todos: Vec<&'a Todo>,
+    /// Word to (position in `todos`, field) to number of occurrences.
+    postings: BTreeMap<String, BTreeMap<(usize, Field), usize>>,
+}
+
+impl<'a> Index<'a> {
+    pub fn new(todos: impl Iterator<Item = &'a Todo>) -> Self {
+        let mut index = Self::default();
+        for (i, todo) in todos.enumerate() {
+            let fields = [
+                (Field::Title, &todo.title[..]),
+                (Field::Description, &todo.description[..]),
+            ]
+            .into_iter()
+            .chain(todo.labels.iter().map(|x| (Field::Labels, &x[..])));
+            for (field, text) in fields {
+                for word in tokenize(text) {
+                    *index
+                        .postings
+                        .entry(word)
+                        .or_default()
+                        .entry((i, field))
+                        .or_insert(0) += 1;
+                }
+            }
+            index.todos.push(todo);
+        }
+
+        index
+    }
+
+    /// Todos containing every word of `query`, in full or as the start of a longer word,
+    /// best first. A word scores by the field it is in, how often it occurs there and
+    /// how few todos contain it; ties go to the lower id.
+    pub fn search(&self, query: &str) -> Vec<Hit<'a>> {
+        let mut scores: Option<BTreeMap<usize, f64>> = None;
+        for term in tokenize(query) {
+            let matches = self
+                .postings
+                .range(term.clone()..)
+                .take_while(|(word, _)| word.starts_with(&term));
+
+            let mut term_scores = BTreeMap::<usize, f64>::new();
+            for (word, postings) in matches {
+                let exactness = if *word == term { 1.0 } else { PREFIX_FACTOR };
+                let mut todos = postings.keys().map(|&(i, _)| i).collect::<Vec<_>>();
+                todos.dedup();
+                let rarity = (1.0 + self.todos.len() as f64 / todos.len() as f64).ln();
+
+                let mut word_scores = BTreeMap::<usize, f64>::new();
+                for (&(i, field), &count) in postings {
+                    *word_scores.entry(i).or_default() +=
+                        field.weight() * (1.0 + (count as f64).ln()) * rarity * exactness;
+                }
+                // A prefix matching several words of a todo counts its best word only.
+                for (i, score) in word_scores {
+                    let best = term_scores.entry(i).or_default();
+                    *best = best.max(score);
+                }
+            }
+
+            scores = Some(match scores {
+                None => term_scores,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(i, score)| term_scores.get(&i).map(|x| (i, score + x)))
+                    .collect(),
+            });
+        }
+
+        let mut hits = scores
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(i, score)| Hit {
+                todo: self.todos[i],
+                score,
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.todo.id.cmp(&b.todo.id)));
+
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::labels;
+    use crate::todo::{PartialTodo, TodoList};
+    use rstest::*;
+
+    fn create_todo_list() -> TodoList {
+        let mut todo_list = TodoList::new(Vec::new().into_iter());
+        for (title, description, label) in [
+            ("Rotate on-call notes", "", "ops"),
+            ("Write release notes", "mention the Rotated keys", "docs"),
+            ("Fix flaky test", "rotate logs before the run", "ci"),
+            ("Plan offsite", "", "Notes"),
+        ] {
+            todo_list.add(&PartialTodo {
+                title: String::from(title),
+                description: String::from(description),
+                labels: labels(&[label]),
+                ..Default::default()
+            });
+        }
+        todo_list
+    }
+
+    fn ids(todo_list: &TodoList, query: &str) -> Vec<usize> {
+        Index::new(todo_list.list().iter())
+            .search(query)
+            .into_iter()
+            .map(|x| x.todo.id)
+            .collect()
+    }
+
+    #[rstest]
+    #[case("On-call, notes!", vec!["on", "call", "notes"])]
+    #[case("  ", vec![])]
+    #[case("Übung_2", vec!["übung", "2"])]
+    fn should_tokenize(#[case] s: &str, #[case] expected: Vec<&str>) {
+        assert_eq!(tokenize(s).collect::<Vec<_>>(), expected);
+    }
+
+    #[rstest]
+    #[case("rotate", vec![0, 2, 1])]
+    #[case("ROTATE", vec![0, 2, 1])]
+    #[case("rot", vec![0, 1, 2])]
+    #[case("notes", vec![0, 1, 3])]
+    #[case("notes rotate", vec![0, 1])]
+    #[case("ci", vec![2])]
+    #[case("offsite deploy", vec![])]
+    #[case("", vec![])]
+    fn should_rank_todos_containing_every_word(#[case] query: &str, #[case] expected: Vec<usize>) {
+        assert_eq!(ids(&create_todo_list(), query), expected);
+    }
+
+    #[test]
+    fn should_score_exact_words_above_prefixes() {
+        let todo_list = create_todo_list();
+        let index = Index::new(todo_list.list().iter());
+
+        let exact = index.search("rotate");
+        let prefix = index.search("rota");
+        assert_eq!(exact[0].todo.id, 0);
+        assert!(exact[0].score > prefix[0].score, "{exact:?} {prefix:?}");
+    }
+}
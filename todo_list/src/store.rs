@@ -0,0 +1,379 @@
+use crate::lock::{lock_path, FileLock, DEFAULT_LOCK_TIMEOUT};
+use crate::migrate::{migrate, Migration};
+use crate::todo::{Todo, TodoList, SCHEMA_VERSION};
+use crate::Error;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde_json::Value;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Where a [`TodoList`] is kept between runs.
+///
+/// Subcommands load the whole list, change it in memory and then report what they changed
+/// through `insert`/`update`/`delete`. Those default to rewriting everything with `save`;
+/// stores that can change single todos in place override them. Subcommands that write hold
+/// [`TodoStore::lock`] from before the load until after the write.
+pub trait TodoStore {
+    /// Takes the store's exclusive lock, waiting a while for other processes to release it.
+    fn lock(&self) -> Result<FileLock, Error>;
+
+    /// Reads the stored list, failing if the store does not exist yet.
+    fn load(&self) -> Result<TodoList, Error>;
+
+    /// Like [`TodoStore::load`], but a missing store is an empty list.
+    fn load_or_new(&self) -> Result<TodoList, Error>;
+
+    /// Replaces the stored list with `todo_list`.
+    fn save(&mut self, todo_list: &TodoList) -> Result<(), Error>;
+
+    /// Stores the todos with `ids`, which were just added to `todo_list`.
+    fn insert(&mut self, todo_list: &TodoList, ids: &[usize]) -> Result<(), Error> {
+        let _ = ids;
+        self.save(todo_list)
+    }
+
+    /// Stores the todos with `ids`, which were changed in `todo_list`.
+    fn update(&mut self, todo_list: &TodoList, ids: &[usize]) -> Result<(), Error> {
+        let _ = ids;
+        self.save(todo_list)
+    }
+
+    /// Forgets the todos with `ids`, which were removed from `todo_list`.
+    fn delete(&mut self, todo_list: &TodoList, ids: &[usize]) -> Result<(), Error> {
+        let _ = ids;
+        self.save(todo_list)
+    }
+
+    /// Upgrades the store to the current schema, or only reports what that would change
+    /// when `dry_run` is set.
+    fn migrate(&mut self, dry_run: bool) -> Result<Vec<Migration>, Error>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StoreKind {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+impl StoreKind {
+    pub fn default_path(&self) -> &'static str {
+        match self {
+            Self::Json => "todo-list.json",
+            Self::Sqlite => "todo-list.db",
+        }
+    }
+
+    pub fn open(&self, path: &str) -> Box<dyn TodoStore> {
+        match self {
+            Self::Json => Box::new(JsonStore::new(path)),
+            Self::Sqlite => Box::new(SqliteStore::new(path)),
+        }
+    }
+}
+
+impl fmt::Display for StoreKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Json => "json",
+                Self::Sqlite => "sqlite",
+            }
+        )
+    }
+}
+
+impl FromStr for StoreKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "sqlite" => Ok(Self::Sqlite),
+            _ => Err(format!("invalid store; {}", s)),
+        }
+    }
+}
+
+/// The whole list as one JSON file, rewritten atomically on every change.
+pub struct JsonStore {
+    path: String,
+}
+
+impl JsonStore {
+    pub fn new(path: &str) -> Self {
+        JsonStore {
+            path: path.to_string(),
+        }
+    }
+}
+
+impl TodoStore for JsonStore {
+    fn lock(&self) -> Result<FileLock, Error> {
+        FileLock::acquire(&lock_path(Path::new(&self.path)), DEFAULT_LOCK_TIMEOUT)
+    }
+
+    fn load(&self) -> Result<TodoList, Error> {
+        TodoList::load(&self.path)
+    }
+
+    fn load_or_new(&self) -> Result<TodoList, Error> {
+        TodoList::load_or_new(&self.path)
+    }
+
+    fn save(&mut self, todo_list: &TodoList) -> Result<(), Error> {
+        todo_list.save(&self.path)
+    }
+
+    fn migrate(&mut self, dry_run: bool) -> Result<Vec<Migration>, Error> {
+        let file = File::open(&self.path).map_err(Error::io(&self.path))?;
+        let mut value: Value = serde_json::from_reader(BufReader::new(file))?;
+        let migrations = migrate(&mut value)?;
+
+        if !dry_run && !migrations.is_empty() {
+            self.load()?.save(&self.path)?;
+        }
+
+        Ok(migrations)
+    }
+}
+
+/// One row per todo in an SQLite database, so a change only touches the rows involved.
+/// Each row holds the todo as JSON, which keeps older rows readable as fields are added.
+pub struct SqliteStore {
+    path: PathBuf,
+}
+
+const SQLITE_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value INTEGER NOT NULL);
+    CREATE TABLE IF NOT EXISTS todos (id INTEGER PRIMARY KEY, todo TEXT NOT NULL);
+";
+
+impl SqliteStore {
+    pub fn new(path: &str) -> Self {
+        SqliteStore {
+            path: PathBuf::from(path),
+        }
+    }
+
+    fn open(&self, create: bool) -> Result<Connection, Error> {
+        if !create {
+            // Surfaces a missing database as the same io::Error the JSON store gives.
+            fs::metadata(&self.path).map_err(Error::io(&self.path))?;
+        }
+        let mut flags = OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        if create {
+            flags |= OpenFlags::SQLITE_OPEN_CREATE;
+        }
+
+        let conn = Connection::open_with_flags(&self.path, flags)?;
+        conn.execute_batch(SQLITE_SCHEMA)?;
+        match Self::meta(&conn, "version")? {
+            Some(x) if x > SCHEMA_VERSION as usize => {
+                return Err(Error::Schema(format!("unsupported file version; {}", x)))
+            }
+            Some(_) => (),
+            None => Self::set_meta(&conn, "version", SCHEMA_VERSION as usize)?,
+        }
+
+        Ok(conn)
+    }
+
+    fn meta(conn: &Connection, key: &str) -> Result<Option<usize>, Error> {
+        let value: Option<i64> = conn
+            .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        value
+            .map(|x| {
+                usize::try_from(x)
+                    .map_err(|_| Error::Schema(format!("invalid {} in database; {}", key, x)))
+            })
+            .transpose()
+    }
+
+    fn set_meta(conn: &Connection, key: &str, value: usize) -> Result<(), Error> {
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+            params![key, to_sql_int(value)?],
+        )?;
+        Ok(())
+    }
+
+    fn read(conn: &Connection) -> Result<TodoList, Error> {
+        let todos = conn
+            .prepare("SELECT todo FROM todos ORDER BY id")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .map(|x| Ok(serde_json::from_str::<Todo>(&x?)?))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let next_id = Self::meta(conn, "next_id")?.unwrap_or(0);
+
+        Ok(TodoList::with_next_id(todos.into_iter(), next_id))
+    }
+
+    /// Writes the given todos of `todo_list` and its id counter in one transaction,
+    /// after deleting the rows for `deleted`, or every row if it is `None`.
+    fn write<'a>(
+        &self,
+        todo_list: &TodoList,
+        todos: impl Iterator<Item = &'a Todo>,
+        deleted: Option<&[usize]>,
+    ) -> Result<(), Error> {
+        let mut conn = self.open(true)?;
+        let tx = conn.transaction()?;
+        match deleted {
+            Some(ids) => {
+                for &id in ids {
+                    tx.execute("DELETE FROM todos WHERE id = ?1", [to_sql_int(id)?])?;
+                }
+            }
+            None => {
+                tx.execute("DELETE FROM todos", [])?;
+            }
+        }
+        for todo in todos {
+            tx.execute(
+                "INSERT OR REPLACE INTO todos (id, todo) VALUES (?1, ?2)",
+                params![to_sql_int(todo.id)?, serde_json::to_string(todo)?],
+            )?;
+        }
+        Self::set_meta(&tx, "next_id", todo_list.next_id())?;
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+fn to_sql_int(value: usize) -> Result<i64, Error> {
+    i64::try_from(value).map_err(|_| Error::Invalid(format!("too large to store; {}", value)))
+}
+
+impl TodoStore for SqliteStore {
+    fn lock(&self) -> Result<FileLock, Error> {
+        FileLock::acquire(&lock_path(&self.path), DEFAULT_LOCK_TIMEOUT)
+    }
+
+    fn load(&self) -> Result<TodoList, Error> {
+        Self::read(&self.open(false)?)
+    }
+
+    fn load_or_new(&self) -> Result<TodoList, Error> {
+        Self::read(&self.open(true)?)
+    }
+
+    fn save(&mut self, todo_list: &TodoList) -> Result<(), Error> {
+        self.write(todo_list, todo_list.list().iter(), None)
+    }
+
+    fn insert(&mut self, todo_list: &TodoList, ids: &[usize]) -> Result<(), Error> {
+        let todos = ids.iter().filter_map(|&id| todo_list.get(id));
+        self.write(todo_list, todos, Some(&[]))
+    }
+
+    fn update(&mut self, todo_list: &TodoList, ids: &[usize]) -> Result<(), Error> {
+        let todos = ids.iter().filter_map(|&id| todo_list.get(id));
+        self.write(todo_list, todos, Some(&[]))
+    }
+
+    fn delete(&mut self, todo_list: &TodoList, ids: &[usize]) -> Result<(), Error> {
+        self.write(todo_list, std::iter::empty(), Some(ids))
+    }
+
+    /// Tables are created at the current schema and rows are read leniently, so there is
+    /// nothing to upgrade beyond checking the stored version.
+    fn migrate(&mut self, _dry_run: bool) -> Result<Vec<Migration>, Error> {
+        self.open(false)?;
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::{PartialTodo, Status, TodoPatch};
+    use rstest::*;
+    use tempfile::tempdir;
+
+    fn partial_todo(title: &str) -> PartialTodo {
+        PartialTodo {
+            title: String::from(title),
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case(StoreKind::Json)]
+    #[case(StoreKind::Sqlite)]
+    fn should_persist_fine_grained_changes(#[case] kind: StoreKind) {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(kind.default_path());
+        let mut store = kind.open(path.to_str().unwrap());
+
+        let mut todo_list = store.load_or_new().unwrap();
+        let ids = [0, 1, 2].map(|x| todo_list.add(&partial_todo(&format!("title{}", x))));
+        store.insert(&todo_list, &ids).unwrap();
+
+        let patch = TodoPatch {
+            status: Some(Status::Done),
+            ..Default::default()
+        };
+        todo_list.update(1, &patch).unwrap();
+        store.update(&todo_list, &[1]).unwrap();
+        todo_list.remove(&[2]);
+        store.delete(&todo_list, &[2]).unwrap();
+
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded, todo_list);
+        assert_eq!(loaded.next_id(), 3);
+    }
+
+    #[rstest]
+    #[case(StoreKind::Json)]
+    #[case(StoreKind::Sqlite)]
+    fn should_replace_everything_on_save(#[case] kind: StoreKind) {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(kind.default_path());
+        let mut store = kind.open(path.to_str().unwrap());
+        let mut todo_list = store.load_or_new().unwrap();
+        todo_list.add(&partial_todo("title0"));
+        store.save(&todo_list).unwrap();
+
+        let todo_list = TodoList::load("test_assets/todo-list.json").unwrap();
+        store.save(&todo_list).unwrap();
+
+        assert_eq!(store.load().unwrap(), todo_list);
+    }
+
+    #[rstest]
+    #[case(StoreKind::Json)]
+    #[case(StoreKind::Sqlite)]
+    fn should_return_io_error_if_store_is_missing(#[case] kind: StoreKind) {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(kind.default_path());
+        let store = kind.open(path.to_str().unwrap());
+
+        let res = store.load();
+
+        match res {
+            Err(err @ Error::Io { .. }) => {
+                assert!(err.to_string().starts_with(path.to_str().unwrap()))
+            }
+            x => panic!("did not return io::Error; {:?} was returned", x),
+        }
+        assert!(!path.exists());
+    }
+
+    #[rstest]
+    #[case("json", Ok(StoreKind::Json))]
+    #[case("sqlite", Ok(StoreKind::Sqlite))]
+    #[case("yaml", Err(String::from("invalid store; yaml")))]
+    fn should_parse_store_kind(#[case] s: &str, #[case] expected: Result<StoreKind, String>) {
+        assert_eq!(s.parse::<StoreKind>(), expected);
+    }
+}
@@ -1,51 +1,116 @@
-use crate::todo::TodoList;
+use crate::args::{parse_id, Opt, Spec};
+use crate::config::Config;
+use crate::date::{parse_date, today};
+use crate::recurrence::Recurrence;
+use crate::store::TodoStore;
+use crate::todo::{split_labels, Labels, PartialTodo, Priority};
+use crate::Error;
+use chrono::NaiveDate;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Default)]
 pub struct AddOptions {
     title: String,
     description: String,
-    label: String,
+    labels: Labels,
+    due: Option<NaiveDate>,
+    priority: Priority,
+    parent: Option<usize>,
+    recurrence: Option<Recurrence>,
 }
 
-pub fn add_parser(mut args: impl Iterator<Item = String>) -> Result<AddOptions, String> {
-    let mut title: Option<String> = None;
-    let mut description: Option<String> = None;
-    let mut label: Option<String> = None;
-
-    while let Some(option) = args.next() {
-        match &option[..] {
-            "-t" | "--title" => {
-                title = args.next();
-            }
-            "-d" | "--description" => {
-                description = args.next();
-            }
-            "-l" | "--label" => {
-                label = args.next();
-            }
-            _ => return Err(format!("invalid option; {}", option)),
-        }
-    }
+pub const SPEC: Spec = Spec {
+    about: "Adds a todo and prints its id.",
+    positionals: "",
+    options: &[
+        Opt::value(Some('t'), "title", "<title>", "title of the todo").required(),
+        Opt::value(
+            Some('d'),
+            "description",
+            "<description>",
+            "longer description",
+        ),
+        Opt::value(
+            Some('l'),
+            "label",
+            "<label>[,<label>...]",
+            "labels to attach; default_label from the config if none",
+        )
+        .repeated(),
+        Opt::value(None, "due", "<date>", "date the todo is due"),
+        Opt::value(
+            Some('p'),
+            "priority",
+            "low|normal|high|urgent",
+            "priority; normal by default",
+        ),
+        Opt::value(None, "parent", "<id>", "make it a subtask of this todo"),
+        Opt::value(
+            None,
+            "repeat",
+            "<rule>",
+            "add the next occurrence once done: daily, weekly, monthly, every <n> days|weeks|months \
+             or <n>d|w|m, optionally followed by \"after completion\"",
+        ),
+    ],
+};
+
+pub fn add_parser(
+    args: impl Iterator<Item = String>,
+    config: &Config,
+) -> Result<AddOptions, Error> {
+    let matches = SPEC.parse(args)?;
+    let labels = match config.default_label() {
+        Some(x) if !matches.flag("label") => split_labels(&x).collect(),
+        _ => matches.values("label").flat_map(split_labels).collect(),
+    };
 
     Ok(AddOptions {
-        title: match title {
-            Some(x) => x,
-            None => return Err(String::from("-t option required")),
-        },
-        description: description.unwrap_or_default(),
-        label: label.unwrap_or_default(),
+        title: matches.value("title").unwrap_or_default().to_string(),
+        description: matches.value("description").unwrap_or_default().to_string(),
+        labels,
+        due: matches.parse_with("due", |x| parse_date(x, today()))?,
+        priority: matches.parse("priority")?.unwrap_or_default(),
+        parent: matches.parse_with("parent", parse_id)?,
+        recurrence: matches.parse("repeat")?,
     })
 }
 
-pub fn add(options: AddOptions) -> Result<(), String> {
-    // TODO: impl
+impl From<AddOptions> for PartialTodo {
+    fn from(options: AddOptions) -> Self {
+        PartialTodo {
+            title: options.title,
+            description: options.description,
+            labels: options.labels,
+            due: options.due,
+            priority: options.priority,
+            parent: options.parent,
+            recurrence: options.recurrence,
+        }
+    }
+}
+
+pub fn add(options: AddOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let _lock = store.lock()?;
+    let mut todo_list = store.load_or_new()?;
+    if let Some(parent) = options.parent {
+        todo_list.check_parent(None, parent)?;
+    }
+    let id = todo_list.add(&PartialTodo::from(options));
+    store.insert(&todo_list, &[id])?;
+
+    println!("{}", id);
+
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::split_into_string_iter;
+    use crate::config::Key;
+    use crate::date::today;
+    use crate::store::JsonStore;
+    use crate::test_utils::{copy_todo_list, labels, split_into_string_iter};
+    use crate::todo::TodoList;
     use regex::Regex;
     use rstest::*;
 
@@ -53,7 +118,8 @@ mod tests {
         AddOptions {
             title: "title1".to_string(),
             description: "description1".to_string(),
-            label: "label1".to_string(),
+            labels: labels(&["label1"]),
+            ..Default::default()
         }
     }
     mod parse_args_tests {
@@ -69,22 +135,52 @@ mod tests {
             Ok(AddOptions {
                 title: "title1".to_string(),
                 description: "".to_string(),
-                label: "".to_string(),
+                labels: labels(&[]),
+                ..Default::default()
             })
         )]
         #[case(
             split_into_string_iter("--title title1 --description description1 --label label1"),
             Ok(add_options1())
         )]
+        #[case(
+            split_into_string_iter("--title=title1 -ddescription1 --label=label1"),
+            Ok(add_options1())
+        )]
         #[case(
             split_into_string_iter("-t title1 --description description1 -l label1"),
             Ok(add_options1())
         )]
+        #[case(
+            split_into_string_iter("-t title1 -l a,b -l c --due 2026-11-01 -p high"),
+            Ok(AddOptions {
+                title: "title1".to_string(),
+                labels: labels(&["a", "b", "c"]),
+                due: NaiveDate::from_ymd_opt(2026, 11, 1),
+                priority: Priority::High,
+                ..Default::default()
+            })
+        )]
+        #[case(
+            split_into_string_iter("-t title1 --repeat 2w"),
+            Ok(AddOptions {
+                title: "title1".to_string(),
+                recurrence: "every 2 weeks".parse().ok(),
+                ..Default::default()
+            })
+        )]
+        #[case(
+            split_into_string_iter("-t title1 --repeat yearly"),
+            Err(String::from("--repeat: invalid recurrence; yearly"))
+        )]
         fn should_parse_args(
             #[case] args: impl Iterator<Item = String>,
             #[case] expected: Result<AddOptions, String>,
         ) {
-            assert_eq!(add_parser(args), expected,)
+            assert_eq!(
+                add_parser(args, &Config::default()).map_err(|x| x.to_string()),
+                expected
+            )
         }
 
         #[rstest]
@@ -94,12 +190,66 @@ mod tests {
         fn should_return_error_if_required_option_missing(
             #[case] args: impl Iterator<Item = String>,
         ) {
-            let re = Regex::new(r"-t option required").unwrap();
+            let re = Regex::new(r"-t: option required").unwrap();
+
+            let parse_res = add_parser(args, &Config::default());
+            assert!(parse_res.is_err(), "not err; parse_res={:?}", parse_res);
+
+            let message = &parse_res.unwrap_err().to_string();
+            assert!(re.is_match(message), "message={message}");
+        }
+
+        #[rstest]
+        #[case(split_into_string_iter("-t title1"), labels(&["inbox"]))]
+        #[case(split_into_string_iter("-t title1 -l label1"), labels(&["label1"]))]
+        fn should_apply_default_label_unless_labels_given(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: Labels,
+        ) {
+            let mut config = Config::default();
+            config.set_flag(Key::DefaultLabel, String::from("inbox"));
+
+            assert_eq!(add_parser(args, &config).unwrap().labels, expected);
+        }
+
+        #[test]
+        fn should_parse_relative_due_date() {
+            let parse_res = add_parser(
+                split_into_string_iter("-t title1 --due tomorrow"),
+                &Config::default(),
+            );
+
+            assert_eq!(parse_res.unwrap().due, today().succ_opt());
+        }
+
+        #[rstest]
+        #[case(
+            split_into_string_iter("-t title1 --due someday"),
+            r"invalid date; someday"
+        )]
+        #[case(split_into_string_iter("-t title1 -p asap"), r"invalid priority; asap")]
+        #[case(
+            split_into_string_iter("-t title1 --due"),
+            r"--due: option requires a value"
+        )]
+        #[case(
+            split_into_string_iter("-t -d description1"),
+            r"-t: option requires a value"
+        )]
+        #[case(
+            split_into_string_iter("-t title1 -t title2"),
+            r"-t: option given more than once"
+        )]
+        fn should_return_error_if_invalid_value_given(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] pattern: &str,
+        ) {
+            let re = Regex::new(pattern).unwrap();
 
-            let parse_res = add_parser(args);
+            let parse_res = add_parser(args, &Config::default());
             assert!(parse_res.is_err(), "not err; parse_res={:?}", parse_res);
 
-            let message = &parse_res.unwrap_err();
+            let message = &parse_res.unwrap_err().to_string();
             assert!(re.is_match(message), "message={message}");
         }
 
@@ -108,11 +258,100 @@ mod tests {
         fn should_return_error_if_invalid_option_given(#[case] args: impl Iterator<Item = String>) {
             let re = Regex::new(r"invalid option").unwrap();
 
-            let parse_res = add_parser(args);
+            let parse_res = add_parser(args, &Config::default());
             assert!(parse_res.is_err(), "not err; parse_res={:?}", parse_res);
 
-            let message = &parse_res.unwrap_err();
+            let message = &parse_res.unwrap_err().to_string();
             assert!(re.is_match(message), "message={message}");
         }
     }
+
+    mod add_tests {
+        use super::*;
+        use crate::store::StoreKind;
+        use crate::todo::{Status, Todo};
+        use std::collections::BTreeSet;
+        use std::fs;
+        use std::thread;
+        use tempfile::tempdir;
+
+        #[test]
+        fn should_create_file_and_add_todo() {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("todo-list.json");
+            let path = path.to_str().unwrap();
+
+            let res = add(add_options1(), &mut JsonStore::new(path));
+            assert!(res.is_ok(), "not Ok; res={:?}", res);
+
+            let todo_list = TodoList::load(path).unwrap();
+            let created_at = todo_list.list()[0].created_at;
+            assert!(created_at.is_some(), "created_at not set");
+            assert_eq!(
+                todo_list.list(),
+                &vec![Todo {
+                    id: 0,
+                    title: "title1".to_string(),
+                    description: "description1".to_string(),
+                    status: Status::ToDo,
+                    labels: labels(&["label1"]),
+                    created_at,
+                    updated_at: created_at,
+                    ..Default::default()
+                }]
+            );
+        }
+
+        #[test]
+        fn should_append_todo_to_existing_file() {
+            let (_dir, path) = copy_todo_list();
+
+            let res = add(add_options1(), &mut JsonStore::new(&path));
+            assert!(res.is_ok(), "not Ok; res={:?}", res);
+
+            let todo_list = TodoList::load(&path).unwrap();
+            assert_eq!(todo_list.list().len(), 4);
+            assert_eq!(todo_list.list()[3].id, 3);
+            assert_eq!(todo_list.list()[3].title, "title1");
+        }
+
+        #[test]
+        fn should_return_error_if_file_is_invalid() {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("todo-list.json");
+            fs::copy("test_assets/todo-list-invalid.json", &path).unwrap();
+
+            let res = add(add_options1(), &mut JsonStore::new(path.to_str().unwrap()));
+            assert!(res.is_err(), "not err; res={:?}", res);
+        }
+
+        #[rstest]
+        #[case(StoreKind::Json)]
+        #[case(StoreKind::Sqlite)]
+        fn should_not_lose_concurrent_adds(#[case] kind: StoreKind) {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join(kind.default_path());
+            let path = path.to_str().unwrap();
+
+            thread::scope(|scope| {
+                for _ in 0..8 {
+                    scope.spawn(|| {
+                        for _ in 0..5 {
+                            add(add_options1(), kind.open(path).as_mut()).unwrap();
+                        }
+                    });
+                }
+            });
+
+            let ids = kind
+                .open(path)
+                .load()
+                .unwrap()
+                .list()
+                .iter()
+                .map(|x| x.id)
+                .collect::<BTreeSet<_>>();
+            assert_eq!(ids, (0..40).collect());
+        }
+    }
 }
@@ -0,0 +1,109 @@
+use crate::args::{Opt, Spec};
+use crate::config::Config;
+use crate::date::today;
+use crate::store::TodoStore;
+use crate::table::{display, Column};
+use crate::todo::Todo;
+use crate::Error;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AgendaOptions {
+    overdue_only: bool,
+    wrap: bool,
+    columns: Vec<Column>,
+    date_format: String,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Prints unfinished todos that are overdue, due today or due within the week; \
+            overdue prints only the first group.",
+    positionals: "",
+    options: &[Opt::flag(
+        Some('w'),
+        "wrap",
+        "wrap long values instead of truncating them",
+    )],
+};
+
+pub fn agenda_parser(
+    args: impl Iterator<Item = String>,
+    overdue_only: bool,
+    config: &Config,
+) -> Result<AgendaOptions, Error> {
+    let matches = SPEC.parse(args)?;
+
+    Ok(AgendaOptions {
+        overdue_only,
+        wrap: matches.flag("wrap"),
+        columns: config.columns(),
+        date_format: config.date_format(),
+    })
+}
+
+fn print_section(heading: &str, todos: &[&Todo], options: &AgendaOptions) {
+    println!("{} ({})", heading, todos.len());
+    if !todos.is_empty() {
+        display(
+            todos.iter().copied(),
+            &options.columns,
+            options.wrap,
+            &options.date_format,
+        );
+    }
+}
+
+pub fn agenda(options: AgendaOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let todo_list = store.load()?;
+    let agenda = todo_list.agenda(today());
+
+    print_section("overdue", &agenda.overdue, &options);
+    if !options.overdue_only {
+        print_section("today", &agenda.today, &options);
+        print_section("this week", &agenda.this_week, &options);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::DATE_FORMAT;
+    use crate::test_utils::split_into_string_iter;
+    use regex::Regex;
+    use rstest::*;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(std::iter::empty(), false, false)]
+        #[case(split_into_string_iter("--wrap"), true, true)]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] overdue_only: bool,
+            #[case] wrap: bool,
+        ) {
+            assert_eq!(
+                agenda_parser(args, overdue_only, &Config::default()).unwrap(),
+                AgendaOptions {
+                    overdue_only,
+                    wrap,
+                    columns: Column::DEFAULT.to_vec(),
+                    date_format: DATE_FORMAT.to_string(),
+                }
+            );
+        }
+
+        #[test]
+        fn should_return_error_if_invalid_option_given() {
+            let re = Regex::new(r"invalid option; -x").unwrap();
+
+            let parse_res = agenda_parser(split_into_string_iter("-x"), false, &Config::default());
+            assert!(parse_res.is_err(), "not err; parse_res={:?}", parse_res);
+
+            let message = &parse_res.unwrap_err().to_string();
+            assert!(re.is_match(message), "message={message}");
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::args::Spec;
+use crate::config::{Config, Key};
+use crate::store::TodoStore;
+use crate::Error;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConfigAction {
+    List,
+    Get(Key),
+    Set(Key, String),
+}
+
+/// `config` is the resolved configuration the action reads or changes.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ConfigOptions {
+    action: ConfigAction,
+    config: Config,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Prints one setting, writes one to the config file (an empty value removes it), \
+            or lists every setting with where its value came from.",
+    positionals: "get <key> | set <key> <value> | list",
+    options: &[],
+};
+
+pub fn config_parser(
+    args: impl Iterator<Item = String>,
+    config: &Config,
+) -> Result<ConfigOptions, Error> {
+    let matches = SPEC.parse(args)?;
+    let mut args = matches.positionals().iter().cloned();
+    let action = match args.next() {
+        Some(x) => x,
+        None => return Err(Error::usage("config action required; get, set or list")),
+    };
+    let mut arg = |name: &str| match args.next() {
+        Some(x) => Ok(x),
+        None => Err(Error::usage(format!("{} required", name))),
+    };
+    let parse_key = |x: String| x.parse::<Key>().map_err(Error::usage);
+
+    let action = match &action[..] {
+        "list" => ConfigAction::List,
+        "get" => ConfigAction::Get(parse_key(arg("key")?)?),
+        "set" => ConfigAction::Set(parse_key(arg("key")?)?, arg("value")?),
+        _ => return Err(Error::usage(format!("invalid config action; {}", action))),
+    };
+
+    match args.next() {
+        Some(x) => Err(Error::usage(format!("unexpected argument; {}", x))),
+        None => Ok(ConfigOptions {
+            action,
+            config: config.clone(),
+        }),
+    }
+}
+
+pub fn config(options: ConfigOptions, _store: &mut dyn TodoStore) -> Result<(), Error> {
+    let mut config = options.config;
+
+    match options.action {
+        ConfigAction::List => {
+            for key in Key::ALL {
+                match config.get(key) {
+                    Some((value, source)) => println!("{}\t{}\t{}", key, value, source),
+                    None => println!("{}\t\tunset", key),
+                }
+            }
+        }
+        ConfigAction::Get(key) => {
+            if let Some((value, _)) = config.get(key) {
+                println!("{}", value);
+            }
+        }
+        ConfigAction::Set(key, value) => {
+            config.set(key, &value)?;
+            config.save()?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::split_into_string_iter;
+    use regex::Regex;
+    use rstest::*;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(split_into_string_iter("list"), ConfigAction::List)]
+        #[case(
+            split_into_string_iter("get date_format"),
+            ConfigAction::Get(Key::DateFormat)
+        )]
+        #[case(
+            split_into_string_iter("set default_label inbox"),
+            ConfigAction::Set(Key::DefaultLabel, String::from("inbox"))
+        )]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: ConfigAction,
+        ) {
+            assert_eq!(
+                config_parser(args, &Config::default()).unwrap().action,
+                expected
+            );
+        }
+
+        #[rstest]
+        #[case(std::iter::empty(), r"config action required")]
+        #[case(split_into_string_iter("get"), r"key required")]
+        #[case(split_into_string_iter("get colour"), r"invalid config key; colour")]
+        #[case(split_into_string_iter("set store"), r"value required")]
+        #[case(split_into_string_iter("list all"), r"unexpected argument; all")]
+        #[case(split_into_string_iter("unset store"), r"invalid config action; unset")]
+        fn should_return_error_if_invalid_args_given(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] pattern: &str,
+        ) {
+            let re = Regex::new(pattern).unwrap();
+
+            let parse_res = config_parser(args, &Config::default());
+            assert!(parse_res.is_err(), "not err; parse_res={:?}", parse_res);
+
+            let message = &parse_res.unwrap_err().to_string();
+            assert!(re.is_match(message), "message={message}");
+        }
+    }
+}
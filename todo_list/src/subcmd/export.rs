@@ -0,0 +1,182 @@
+use crate::args::{Opt, Spec};
+use crate::date::DATE_FORMAT;
+use crate::format::Format;
+use crate::reader::infer_format;
+use crate::store::TodoStore;
+use crate::table::Column;
+use crate::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExportOptions {
+    format: Format,
+    output: Option<String>,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Writes every todo to standard output or a file.",
+    positionals: "",
+    options: &[
+        Opt::value(
+            Some('f'),
+            "format",
+            "json|ndjson|csv|tsv|markdown",
+            "output format; inferred from the output file or json by default",
+        ),
+        Opt::value(
+            Some('o'),
+            "output",
+            "<file>",
+            "file to write instead of standard output",
+        ),
+    ],
+};
+
+pub fn export_parser(args: impl Iterator<Item = String>) -> Result<ExportOptions, Error> {
+    let matches = SPEC.parse(args)?;
+    let output = matches.value("output").map(String::from);
+    let format = matches
+        .parse("format")?
+        .or_else(|| output.as_deref().and_then(infer_format))
+        .unwrap_or(Format::Json);
+
+    Ok(ExportOptions { format, output })
+}
+
+pub fn export(options: ExportOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let todo_list = store.load()?;
+    let todos = todo_list.list().iter().collect::<Vec<_>>();
+
+    let mut out: Box<dyn Write> = match &options.output {
+        Some(x) => Box::new(BufWriter::new(File::create(x).map_err(Error::io(x))?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    options
+        .format
+        .formatter(false, DATE_FORMAT)
+        .write(&todos, &Column::EXPORT, &mut out)?;
+    out.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::read;
+    use crate::store::JsonStore;
+    use crate::subcmd::import::{import, import_parser};
+    use crate::test_utils::split_into_string_iter;
+    use crate::todo::{TodoList, TodoPatch};
+    use rstest::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(std::iter::empty(), ExportOptions { format: Format::Json, output: None })]
+        #[case(
+            split_into_string_iter("-o todos.md"),
+            ExportOptions { format: Format::Markdown, output: Some("todos.md".to_string()) }
+        )]
+        #[case(
+            split_into_string_iter("-f csv -o todos.txt"),
+            ExportOptions { format: Format::Csv, output: Some("todos.txt".to_string()) }
+        )]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: ExportOptions,
+        ) {
+            assert_eq!(export_parser(args).unwrap(), expected);
+        }
+
+        #[rstest]
+        #[case(split_into_string_iter("-f xml"), "-f: invalid format; xml")]
+        #[case(split_into_string_iter("-o"), "-o: option requires a value")]
+        #[case(split_into_string_iter("todos.csv"), "unexpected argument; todos.csv")]
+        fn should_return_error_if_invalid_args_given(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: &str,
+        ) {
+            assert_eq!(export_parser(args).unwrap_err().to_string(), expected);
+        }
+    }
+
+    mod export_tests {
+        use super::*;
+
+        #[test]
+        fn should_export_to_file() {
+            let dir = tempdir().unwrap();
+            let output = dir.path().join("todos.csv");
+
+            export(
+                export_parser(
+                    vec!["-o".to_string(), output.to_str().unwrap().to_string()].into_iter(),
+                )
+                .unwrap(),
+                &mut JsonStore::new("test_assets/todo-list.json"),
+            )
+            .unwrap();
+
+            let records = read(Format::Csv, &fs::read_to_string(output).unwrap()).unwrap();
+            assert_eq!(
+                TodoList::new(records.keyed.into_iter()),
+                TodoList::load("test_assets/todo-list.json").unwrap()
+            );
+        }
+
+        #[rstest]
+        #[case(Format::Csv, "todos.csv")]
+        #[case(Format::Tsv, "todos.tsv")]
+        fn should_export_what_import_reads_back(#[case] format: Format, #[case] name: &str) {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("todo-list.json");
+            let path = path.to_str().unwrap();
+            let mut todo_list = TodoList::load("test_assets/todo-list.json").unwrap();
+            let patch = TodoPatch {
+                parent: Some(Some(0)),
+                recurrence: Some("every 2 weeks after completion".parse().ok()),
+                ..Default::default()
+            };
+            todo_list.update(2, &patch).unwrap();
+            todo_list.link(2, &[0, 1]).unwrap();
+            todo_list.save(path).unwrap();
+
+            let first = dir.path().join(name).to_str().unwrap().to_string();
+            let options = ExportOptions {
+                format,
+                output: Some(first.clone()),
+            };
+            export(options, &mut JsonStore::new(path)).unwrap();
+            let imported = dir.path().join("imported.json");
+            let imported = imported.to_str().unwrap();
+            import(
+                import_parser(vec![first.clone()].into_iter()).unwrap(),
+                &mut JsonStore::new(imported),
+            )
+            .unwrap();
+            let second = dir.path().join("second").to_str().unwrap().to_string();
+            let options = ExportOptions {
+                format,
+                output: Some(second.clone()),
+            };
+            export(options, &mut JsonStore::new(imported)).unwrap();
+
+            let todo_list = TodoList::load(imported).unwrap();
+            assert_eq!(todo_list.get(2).unwrap().parent, Some(0));
+            assert!(todo_list.get(2).unwrap().recurrence.is_some());
+            assert_eq!(
+                todo_list.get(2).unwrap().blocked_by,
+                std::collections::BTreeSet::from([0, 1])
+            );
+            assert_eq!(
+                fs::read_to_string(second).unwrap(),
+                fs::read_to_string(first).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::args::Spec;
+use crate::date::TIMESTAMP_FORMAT;
+use crate::store::TodoStore;
+use crate::Error;
+use chrono::{DateTime, Local, TimeDelta, Utc};
+
+/// `id` is a numeric id or a hash id prefix, resolved once the list is loaded.
+#[derive(Debug, PartialEq, Eq)]
+pub struct HistoryOptions {
+    id: String,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Prints when a todo was created and each status change it went through.",
+    positionals: "<id|hash>",
+    options: &[],
+};
+
+pub fn history_parser(args: impl Iterator<Item = String>) -> Result<HistoryOptions, Error> {
+    match SPEC.parse(args)?.positionals() {
+        [] => Err(Error::usage("id required")),
+        [id] => Ok(HistoryOptions { id: id.clone() }),
+        [_, x, ..] => Err(Error::usage(format!("unexpected argument; {}", x))),
+    }
+}
+
+fn format_timestamp(t: Option<DateTime<Utc>>) -> String {
+    match t {
+        Some(x) => x.with_timezone(&Local).format(TIMESTAMP_FORMAT).to_string(),
+        None => String::from("-"),
+    }
+}
+
+/// Formats a duration as days, hours and minutes, e.g. `2d 3h 15m`, dropping leading zero units.
+fn format_duration(duration: TimeDelta) -> String {
+    let minutes = duration.num_minutes().max(0);
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h {}m", days, hours, minutes),
+    }
+}
+
+pub fn history(options: HistoryOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let todo_list = store.load()?;
+    let todo = todo_list.get(todo_list.resolve(&options.id)?).unwrap();
+
+    println!("{} ({}) {}", todo.id, todo.hash_id(), todo.title);
+    println!("created    {}", format_timestamp(todo.created_at));
+    println!("updated    {}", format_timestamp(todo.updated_at));
+    for change in &todo.history {
+        println!(
+            "{}  {} -> {}",
+            format_timestamp(Some(change.at)),
+            change.from,
+            change.to
+        );
+    }
+    if let Some(completed_at) = todo.completed_at {
+        println!("completed  {}", format_timestamp(Some(completed_at)));
+    }
+    if let Some(cycle_time) = todo.cycle_time() {
+        println!("cycle time {}", format_duration(cycle_time));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::JsonStore;
+    use crate::test_utils::split_into_string_iter;
+    use regex::Regex;
+    use rstest::*;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[test]
+        fn should_parse_args() {
+            assert_eq!(
+                history_parser(split_into_string_iter("3")).unwrap(),
+                HistoryOptions {
+                    id: String::from("3")
+                }
+            );
+        }
+
+        #[rstest]
+        #[case(std::iter::empty(), r"id required")]
+        #[case(split_into_string_iter("1 2"), r"unexpected argument; 2")]
+        fn should_return_error_if_invalid_args_given(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] pattern: &str,
+        ) {
+            let re = Regex::new(pattern).unwrap();
+
+            let parse_res = history_parser(args);
+            assert!(parse_res.is_err(), "not err; parse_res={:?}", parse_res);
+
+            let message = &parse_res.unwrap_err().to_string();
+            assert!(re.is_match(message), "message={message}");
+        }
+    }
+
+    mod history_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(TimeDelta::seconds(59), "0m")]
+        #[case(TimeDelta::minutes(135), "2h 15m")]
+        #[case(TimeDelta::minutes(3 * 24 * 60 + 5), "3d 0h 5m")]
+        fn should_format_duration(#[case] duration: TimeDelta, #[case] expected: &str) {
+            assert_eq!(format_duration(duration), expected);
+        }
+
+        #[test]
+        fn should_return_error_if_todo_not_found() {
+            let res = history(
+                HistoryOptions {
+                    id: String::from("9"),
+                },
+                &mut JsonStore::new("test_assets/todo-list.json"),
+            );
+
+            assert!(res.is_err(), "not err; res={:?}", res);
+            assert_eq!(res.unwrap_err().to_string(), "todo not found; id=9");
+        }
+    }
+}
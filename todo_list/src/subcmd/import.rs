@@ -0,0 +1,227 @@
+use crate::args::{Opt, Spec};
+use crate::format::Format;
+use crate::reader::{infer_format, read};
+use crate::store::TodoStore;
+use crate::todo::Conflict;
+use crate::Error;
+use std::fs;
+use std::io::{self, Read};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ImportOptions {
+    source: String,
+    format: Format,
+    on_conflict: Conflict,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Adds the todos in a file, or in standard input if it is -, and prints their ids. \
+            Links between them follow renumbered ids; links to todos in neither the file \
+            nor the list, or that would form a cycle, are dropped and listed as detached.",
+    positionals: "<file|->",
+    options: &[
+        Opt::value(
+            Some('f'),
+            "format",
+            "json|ndjson|csv|tsv|markdown",
+            "input format; inferred from the file extension by default",
+        ),
+        Opt::value(
+            None,
+            "on-conflict",
+            "renumber|skip|overwrite",
+            "what to do with an id already in use; renumber by default",
+        ),
+    ],
+};
+
+pub fn import_parser(args: impl Iterator<Item = String>) -> Result<ImportOptions, Error> {
+    let matches = SPEC.parse(args)?;
+    let source = match matches.positionals() {
+        [] => return Err(Error::usage("file required")),
+        [x] => x.clone(),
+        [_, x, ..] => return Err(Error::usage(format!("unexpected argument; {}", x))),
+    };
+    let format = match matches.parse("format")?.or_else(|| infer_format(&source)) {
+        Some(Format::Table) => {
+            return Err(Error::invalid_argument(
+                "-f",
+                "table format cannot be imported",
+            ))
+        }
+        Some(x) => x,
+        None => {
+            return Err(Error::invalid_argument(
+                "-f",
+                format!("option required; cannot infer format of {}", source),
+            ))
+        }
+    };
+
+    Ok(ImportOptions {
+        source,
+        format,
+        on_conflict: matches.parse("on-conflict")?.unwrap_or(Conflict::Renumber),
+    })
+}
+
+pub fn import(options: ImportOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let input = if options.source == "-" {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        input
+    } else {
+        fs::read_to_string(&options.source).map_err(Error::io(&options.source))?
+    };
+    let records = read(options.format, &input)?;
+
+    let _lock = store.lock()?;
+    let mut todo_list = store.load_or_new()?;
+    let mut report = todo_list.import(records.keyed, options.on_conflict);
+    report.added.extend(todo_list.append(records.unkeyed));
+    let stored = report
+        .added
+        .iter()
+        .chain(report.renumbered.iter().map(|(_, to)| to))
+        .chain(&report.overwritten)
+        .copied()
+        .collect::<Vec<_>>();
+    store.insert(&todo_list, &stored)?;
+
+    let join = |ids: &[usize]| {
+        ids.iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if !report.added.is_empty() {
+        println!("added: {}", join(&report.added));
+    }
+    if !report.renumbered.is_empty() {
+        println!(
+            "renumbered: {}",
+            report
+                .renumbered
+                .iter()
+                .map(|(from, to)| format!("{} -> {}", from, to))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    if !report.skipped.is_empty() {
+        println!("skipped: {}", join(&report.skipped));
+    }
+    if !report.overwritten.is_empty() {
+        println!("overwritten: {}", join(&report.overwritten));
+    }
+    if !report.detached.is_empty() {
+        println!("detached: {}", join(&report.detached));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::JsonStore;
+    use crate::test_utils::split_into_string_iter;
+    use crate::todo::TodoList;
+    use regex::Regex;
+    use rstest::*;
+    use tempfile::tempdir;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(
+            split_into_string_iter("todos.md"),
+            ImportOptions {
+                source: "todos.md".to_string(),
+                format: Format::Markdown,
+                on_conflict: Conflict::Renumber,
+            }
+        )]
+        #[case(
+            split_into_string_iter("- -f csv --on-conflict skip"),
+            ImportOptions {
+                source: "-".to_string(),
+                format: Format::Csv,
+                on_conflict: Conflict::Skip,
+            }
+        )]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: ImportOptions,
+        ) {
+            assert_eq!(import_parser(args).unwrap(), expected);
+        }
+
+        #[rstest]
+        #[case(std::iter::empty(), r"file required")]
+        #[case(split_into_string_iter("todos"), r"-f: option required")]
+        #[case(split_into_string_iter("todos.txt -f table"), r"cannot be imported")]
+        #[case(split_into_string_iter("a.md b.md"), r"unexpected argument; b.md")]
+        #[case(
+            split_into_string_iter("a.md --on-conflict merge"),
+            r"invalid conflict policy"
+        )]
+        fn should_return_error_if_invalid_args_given(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] pattern: &str,
+        ) {
+            let re = Regex::new(pattern).unwrap();
+
+            let parse_res = import_parser(args);
+            assert!(parse_res.is_err(), "not err; parse_res={:?}", parse_res);
+
+            let message = &parse_res.unwrap_err().to_string();
+            assert!(re.is_match(message), "message={message}");
+        }
+    }
+
+    mod import_tests {
+        use super::*;
+
+        #[test]
+        fn should_import_markdown_and_json_with_renumbering() {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("todo-list.json");
+            let path = path.to_str().unwrap();
+            let md_path = dir.path().join("todos.md");
+            fs::write(&md_path, "# infra\n- [ ] title3\n- [x] title4\n").unwrap();
+
+            import(
+                import_parser(vec![md_path.to_str().unwrap().to_string()].into_iter()).unwrap(),
+                &mut JsonStore::new(path),
+            )
+            .unwrap();
+            import(
+                import_parser(split_into_string_iter("test_assets/todo-list.json")).unwrap(),
+                &mut JsonStore::new(path),
+            )
+            .unwrap();
+
+            let todo_list = TodoList::load(path).unwrap();
+            assert_eq!(
+                todo_list
+                    .list()
+                    .iter()
+                    .map(|x| (
+                        x.id,
+                        &x.title[..],
+                        x.labels.iter().next().map(String::as_str)
+                    ))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (0, "title3", Some("infra")),
+                    (1, "title4", Some("infra")),
+                    (2, "title0", None),
+                    (3, "title1", Some("label1")),
+                    (4, "title2", Some("label2")),
+                ]
+            );
+        }
+    }
+}
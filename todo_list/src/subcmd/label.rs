@@ -0,0 +1,208 @@
+use crate::args::Spec;
+use crate::store::TodoStore;
+use crate::todo::TodoList;
+use crate::Error;
+
+/// `ids` are numeric ids or hash id prefixes, resolved once the list is loaded.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LabelOptions {
+    List,
+    Add { label: String, ids: Vec<String> },
+    Rm { label: String, ids: Vec<String> },
+    Rename { from: String, to: String },
+}
+
+fn resolve_ids(todo_list: &TodoList, ids: &[String]) -> Result<Vec<usize>, Error> {
+    ids.iter().map(|x| todo_list.resolve(x)).collect()
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Adds a label to todos, removes it from some or all todos, renames it everywhere, \
+            or lists every label with its count.",
+    positionals: "add <label> <id|hash>... | rm <label> [<id|hash>...] | rename <old> <new> | list",
+    options: &[],
+};
+
+pub fn label_parser(args: impl Iterator<Item = String>) -> Result<LabelOptions, Error> {
+    let matches = SPEC.parse(args)?;
+    let mut args = matches.positionals().iter().cloned();
+    let action = match args.next() {
+        Some(x) => x,
+        None => {
+            return Err(Error::usage(
+                "label action required; add, rm, rename or list",
+            ))
+        }
+    };
+    let mut arg = |name: &str| match args.next() {
+        Some(x) => Ok(x),
+        None => Err(Error::usage(format!("{} required", name))),
+    };
+
+    let options = match &action[..] {
+        "list" => LabelOptions::List,
+        "add" => {
+            let label = arg("label")?;
+            let ids = args.collect::<Vec<_>>();
+            if ids.is_empty() {
+                return Err(Error::usage("id required"));
+            }
+            return Ok(LabelOptions::Add { label, ids });
+        }
+        "rm" => {
+            let label = arg("label")?;
+            return Ok(LabelOptions::Rm {
+                label,
+                ids: args.collect(),
+            });
+        }
+        "rename" => LabelOptions::Rename {
+            from: arg("old label")?,
+            to: arg("new label")?,
+        },
+        _ => return Err(Error::usage(format!("invalid label action; {}", action))),
+    };
+
+    match args.next() {
+        Some(x) => Err(Error::usage(format!("unexpected argument; {}", x))),
+        None => Ok(options),
+    }
+}
+
+pub fn label(options: LabelOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let _lock = store.lock()?;
+    let mut todo_list = store.load()?;
+
+    let changed = match options {
+        LabelOptions::List => {
+            for (label, count) in todo_list.labels() {
+                println!("{}\t{}", label, count);
+            }
+            return Ok(());
+        }
+        LabelOptions::Add { label, ids } => {
+            let ids = resolve_ids(&todo_list, &ids)?;
+            let changed = todo_list.add_label(&label, &ids)?;
+            println!("added {} to {} todo(s)", label, changed.len());
+            changed
+        }
+        LabelOptions::Rm { label, ids } => {
+            let ids = resolve_ids(&todo_list, &ids)?;
+            let changed = todo_list.remove_label(&label, &ids)?;
+            println!("removed {} from {} todo(s)", label, changed.len());
+            changed
+        }
+        LabelOptions::Rename { from, to } => {
+            let changed = todo_list.rename_label(&from, &to)?;
+            println!("renamed {} to {} on {} todo(s)", from, to, changed.len());
+            changed
+        }
+    };
+    if !changed.is_empty() {
+        store.update(&todo_list, &changed)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::JsonStore;
+    use crate::test_utils::{copy_todo_list, labels, split_into_string_iter};
+    use crate::todo::TodoList;
+    use rstest::*;
+    use std::fs;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(split_into_string_iter("list"), LabelOptions::List)]
+        #[case(
+            split_into_string_iter("add infra 1 2"),
+            LabelOptions::Add { label: "infra".to_string(), ids: vec![String::from("1"), String::from("2")] }
+        )]
+        #[case(
+            split_into_string_iter("rm infra"),
+            LabelOptions::Rm { label: "infra".to_string(), ids: vec![] }
+        )]
+        #[case(
+            split_into_string_iter("rm infra 3"),
+            LabelOptions::Rm { label: "infra".to_string(), ids: vec![String::from("3")] }
+        )]
+        #[case(
+            split_into_string_iter("rename infra ops"),
+            LabelOptions::Rename { from: "infra".to_string(), to: "ops".to_string() }
+        )]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: LabelOptions,
+        ) {
+            assert_eq!(label_parser(args).unwrap(), expected);
+        }
+
+        #[rstest]
+        #[case(std::iter::empty(), "label action required; add, rm, rename or list")]
+        #[case(split_into_string_iter("tag"), "invalid label action; tag")]
+        #[case(split_into_string_iter("add infra"), "id required")]
+        #[case(split_into_string_iter("rename infra"), "new label required")]
+        #[case(split_into_string_iter("list all"), "unexpected argument; all")]
+        fn should_return_error_if_invalid_args_given(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: &str,
+        ) {
+            assert_eq!(label_parser(args).unwrap_err().to_string(), expected);
+        }
+    }
+
+    mod label_tests {
+        use super::*;
+
+        #[test]
+        fn should_add_and_rename_labels() {
+            let (_dir, path) = copy_todo_list();
+
+            label(
+                label_parser(split_into_string_iter("add label1 0 2")).unwrap(),
+                &mut JsonStore::new(&path),
+            )
+            .unwrap();
+            label(
+                label_parser(split_into_string_iter("rename label1 infra")).unwrap(),
+                &mut JsonStore::new(&path),
+            )
+            .unwrap();
+
+            let todo_list = TodoList::load(&path).unwrap();
+            assert_eq!(
+                todo_list
+                    .list()
+                    .iter()
+                    .map(|x| x.labels.clone())
+                    .collect::<Vec<_>>(),
+                vec![
+                    labels(&["infra"]),
+                    labels(&["infra"]),
+                    labels(&["infra", "label2"])
+                ]
+            );
+        }
+
+        #[test]
+        fn should_not_save_if_id_not_found() {
+            let (_dir, path) = copy_todo_list();
+
+            let res = label(
+                label_parser(split_into_string_iter("add infra 0 9")).unwrap(),
+                &mut JsonStore::new(&path),
+            );
+
+            assert!(res.is_err(), "not err; res={:?}", res);
+            assert_eq!(
+                fs::read_to_string(path).unwrap(),
+                fs::read_to_string("test_assets/todo-list.json").unwrap()
+            );
+        }
+    }
+}
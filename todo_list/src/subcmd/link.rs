@@ -0,0 +1,181 @@
+use crate::args::Spec;
+use crate::store::TodoStore;
+use crate::Error;
+
+/// `unlink` is set for the `unlink` subcommand, which removes the given blockers. `id` and
+/// `blockers` are numeric ids or hash id prefixes, resolved once the list is loaded.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LinkOptions {
+    id: String,
+    blockers: Vec<String>,
+    unlink: bool,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Marks a todo as blocked by other todos, refusing links that would form a cycle.",
+    positionals: "<id|hash> <blocker>...",
+    options: &[],
+};
+
+pub const UNLINK_SPEC: Spec = Spec {
+    about: "Removes the given blockers from a todo.",
+    positionals: "<id|hash> <blocker>...",
+    options: &[],
+};
+
+pub fn link_parser(args: impl Iterator<Item = String>, unlink: bool) -> Result<LinkOptions, Error> {
+    let spec = if unlink { &UNLINK_SPEC } else { &SPEC };
+    let matches = spec.parse(args)?;
+    let (id, blockers) = match matches.positionals() {
+        [id, blockers @ ..] => (id.clone(), blockers.to_vec()),
+        [] => return Err(Error::usage("id required")),
+    };
+
+    if blockers.is_empty() {
+        return Err(Error::usage("blocker id required"));
+    }
+
+    Ok(LinkOptions {
+        id,
+        blockers,
+        unlink,
+    })
+}
+
+pub fn link(options: LinkOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let _lock = store.lock()?;
+    let mut todo_list = store.load()?;
+    let id = todo_list.resolve(&options.id)?;
+    let blockers = options
+        .blockers
+        .iter()
+        .map(|x| todo_list.resolve(x))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if options.unlink {
+        let count = todo_list.unlink(id, &blockers)?;
+        println!("unlinked {} blocker(s) from {}", count, id);
+    } else {
+        let count = todo_list.link(id, &blockers)?;
+        println!("linked {} blocker(s) to {}", count, id);
+    }
+    store.update(&todo_list, &[id])?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::JsonStore;
+    use crate::test_utils::{copy_todo_list, split_into_string_iter};
+    use crate::todo::TodoList;
+    use rstest::*;
+    use std::collections::BTreeSet;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(
+            split_into_string_iter("7 3 5"),
+            false,
+            LinkOptions { id: String::from("7"), blockers: vec![String::from("3"), String::from("5")], unlink: false }
+        )]
+        #[case(
+            split_into_string_iter("7 3"),
+            true,
+            LinkOptions { id: String::from("7"), blockers: vec![String::from("3")], unlink: true }
+        )]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] unlink: bool,
+            #[case] expected: LinkOptions,
+        ) {
+            assert_eq!(link_parser(args, unlink).unwrap(), expected);
+        }
+
+        #[rstest]
+        #[case(std::iter::empty(), "id required")]
+        #[case(split_into_string_iter("7"), "blocker id required")]
+        fn should_return_error_if_invalid_args_given(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: &str,
+        ) {
+            assert_eq!(link_parser(args, false).unwrap_err().to_string(), expected);
+        }
+    }
+
+    mod link_tests {
+        use super::*;
+
+        #[test]
+        fn should_link_and_unlink_blockers() {
+            let (_dir, path) = copy_todo_list();
+
+            link(
+                link_parser(split_into_string_iter("0 1 2"), false).unwrap(),
+                &mut JsonStore::new(&path),
+            )
+            .unwrap();
+            link(
+                link_parser(split_into_string_iter("0 1"), true).unwrap(),
+                &mut JsonStore::new(&path),
+            )
+            .unwrap();
+
+            let todo_list = TodoList::load(&path).unwrap();
+            assert_eq!(todo_list.get(0).unwrap().blocked_by, BTreeSet::from([2]));
+        }
+
+        #[test]
+        fn should_link_blockers_by_hash_id() {
+            let (_dir, path) = copy_todo_list();
+            let hash = TodoList::load(&path).unwrap().get(2).unwrap().hash_id();
+
+            link(
+                link_parser(
+                    [String::from("0"), hash[..7].to_string()].into_iter(),
+                    false,
+                )
+                .unwrap(),
+                &mut JsonStore::new(&path),
+            )
+            .unwrap();
+
+            let todo_list = TodoList::load(&path).unwrap();
+            assert_eq!(todo_list.get(0).unwrap().blocked_by, BTreeSet::from([2]));
+        }
+
+        #[test]
+        fn should_return_error_if_blocker_not_found() {
+            let (_dir, path) = copy_todo_list();
+
+            let res = link(
+                link_parser(split_into_string_iter("0 y"), false).unwrap(),
+                &mut JsonStore::new(&path),
+            );
+
+            assert_eq!(res.unwrap_err().to_string(), "todo not found; id=y");
+        }
+
+        #[test]
+        fn should_not_save_if_link_would_create_cycle() {
+            let (_dir, path) = copy_todo_list();
+
+            link(
+                link_parser(split_into_string_iter("0 1"), false).unwrap(),
+                &mut JsonStore::new(&path),
+            )
+            .unwrap();
+            let res = link(
+                link_parser(split_into_string_iter("1 2 0"), false).unwrap(),
+                &mut JsonStore::new(&path),
+            );
+            assert!(res.is_err(), "not err; res={:?}", res);
+
+            let todo_list = TodoList::load(&path).unwrap();
+            assert!(todo_list.get(1).unwrap().blocked_by.is_empty());
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use crate::args::{Opt, Spec};
+use crate::config::Config;
+use crate::date::{parse_date, today};
+use crate::format::Format;
+use crate::query::Query;
+use crate::store::TodoStore;
+use crate::table::{parse_columns, Column};
+use crate::Error;
+use regex::Regex;
+use std::io::{self, BufWriter, Write};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ListOptions {
+    query: Query,
+    columns: Vec<Column>,
+    wrap: bool,
+    format: Format,
+    date_format: String,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Prints the todos matching every filter given.",
+    positionals: "",
+    options: &[
+        Opt::value(
+            None,
+            "where",
+            "<query>",
+            "only todos matching the query, e.g. \"status:Done label:infra due<2026-11-01\"",
+        ),
+        Opt::value(
+            Some('s'),
+            "status",
+            "<status>",
+            "only todos with this status",
+        ),
+        Opt::value(Some('l'), "label", "<label>", "only todos with this label"),
+        Opt::value(
+            None,
+            "search",
+            "<regex>",
+            "only todos whose title, description or a label matches",
+        ),
+        Opt::value(
+            None,
+            "since",
+            "<date>",
+            "only todos created on or after the date",
+        ),
+        Opt::value(
+            None,
+            "until",
+            "<date>",
+            "only todos created on or before the date",
+        ),
+        Opt::value(
+            None,
+            "sort",
+            "id|title|status|label|due|priority",
+            "sort key; id by default",
+        ),
+        Opt::flag(Some('r'), "reverse", "reverse the order"),
+        Opt::value(
+            None,
+            "columns",
+            "<column>,...",
+            "table and CSV columns to show; columns from the config by default",
+        ),
+        Opt::flag(
+            Some('w'),
+            "wrap",
+            "wrap long values instead of truncating them",
+        ),
+        Opt::value(
+            Some('f'),
+            "format",
+            "table|json|ndjson|csv|tsv|markdown",
+            "output format; table by default",
+        ),
+    ],
+};
+
+pub fn list_parser(
+    args: impl Iterator<Item = String>,
+    config: &Config,
+) -> Result<ListOptions, Error> {
+    let matches = SPEC.parse(args)?;
+    let query = Query {
+        filter: matches.parse("where")?,
+        status: matches.parse("status")?,
+        label: matches.value("label").map(String::from),
+        search: matches.parse_with("search", |x| {
+            Regex::new(x).map_err(|_| format!("invalid regex; {}", x))
+        })?,
+        since: matches.parse_with("since", |x| parse_date(x, today()))?,
+        until: matches.parse_with("until", |x| parse_date(x, today()))?,
+        sort: matches.parse("sort")?,
+        reverse: matches.flag("reverse"),
+    };
+
+    Ok(ListOptions {
+        query,
+        columns: matches
+            .parse_with("columns", parse_columns)?
+            .unwrap_or_else(|| config.columns()),
+        wrap: matches.flag("wrap"),
+        format: matches.parse("format")?.unwrap_or(Format::Table),
+        date_format: config.date_format(),
+    })
+}
+
+pub fn list(options: ListOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let todo_list = store.load()?;
+    let todos = todo_list.query(&options.query).collect::<Vec<_>>();
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    options
+        .format
+        .formatter(options.wrap, &options.date_format)
+        .write(&todos, &options.columns, &mut out)?;
+    out.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Key;
+    use crate::date::DATE_FORMAT;
+    use crate::query::SortKey;
+    use crate::test_utils::split_into_string_iter;
+    use crate::todo::Status;
+    use chrono::NaiveDate;
+    use regex::Regex;
+    use rstest::*;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(
+            std::iter::empty(),
+            ListOptions {
+                query: Query::default(),
+                columns: Column::DEFAULT.to_vec(),
+                wrap: false,
+                format: Format::Table,
+                date_format: DATE_FORMAT.to_string(),
+            }
+        )]
+        #[case(
+            split_into_string_iter("-s Done --label label1 --search ^t --since 2026-10-01 --until 2026-10-31 --sort title --reverse --wrap -f csv"),
+            ListOptions {
+                query: Query {
+                    filter: None,
+                    status: Some(Status::Done),
+                    label: Some("label1".to_string()),
+                    search: Some(Regex::new("^t").unwrap()),
+                    since: NaiveDate::from_ymd_opt(2026, 10, 1),
+                    until: NaiveDate::from_ymd_opt(2026, 10, 31),
+                    sort: Some(SortKey::Title),
+                    reverse: true,
+                },
+                columns: Column::DEFAULT.to_vec(),
+                wrap: true,
+                format: Format::Csv,
+                date_format: DATE_FORMAT.to_string(),
+            }
+        )]
+        #[case(
+            split_into_string_iter("--columns id,title,status"),
+            ListOptions {
+                query: Query::default(),
+                columns: vec![Column::Id, Column::Title, Column::Status],
+                wrap: false,
+                format: Format::Table,
+                date_format: DATE_FORMAT.to_string(),
+            }
+        )]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: ListOptions,
+        ) {
+            assert_eq!(list_parser(args, &Config::default()).unwrap(), expected);
+        }
+
+        #[rstest]
+        #[case(std::iter::empty(), vec![Column::Id, Column::Due])]
+        #[case(split_into_string_iter("--columns title"), vec![Column::Title])]
+        fn should_take_defaults_from_config(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: Vec<Column>,
+        ) {
+            let mut config = Config::default();
+            config.set_flag(Key::Columns, String::from("id,due"));
+            config.set_flag(Key::DateFormat, String::from("%d/%m"));
+
+            let options = list_parser(args, &config).unwrap();
+            assert_eq!(options.columns, expected);
+            assert_eq!(options.date_format, "%d/%m");
+        }
+
+        #[test]
+        fn should_parse_where_query() {
+            let args = ["--where", "status:Done OR id<1"]
+                .into_iter()
+                .map(String::from);
+
+            let options = list_parser(args, &Config::default()).unwrap();
+            assert_eq!(
+                options.query.filter.map(|x| x.to_string()).as_deref(),
+                Some("status:Done OR id<1")
+            );
+        }
+
+        #[rstest]
+        #[case(
+            split_into_string_iter("--where status:Doing"),
+            r"^--where: invalid status; Doing at column 8\n"
+        )]
+        #[case(split_into_string_iter("--search ("), r"invalid regex; \(")]
+        #[case(split_into_string_iter("--sort size"), r"invalid sort key; size")]
+        #[case(split_into_string_iter("--since someday"), r"invalid date; someday")]
+        #[case(split_into_string_iter("--columns id,size"), r"invalid column; size")]
+        #[case(
+            split_into_string_iter("--status"),
+            r"--status: option requires a value"
+        )]
+        #[case(split_into_string_iter("-x"), r"invalid option; -x")]
+        fn should_return_error_if_invalid_args_given(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] pattern: &str,
+        ) {
+            let re = Regex::new(pattern).unwrap();
+
+            let parse_res = list_parser(args, &Config::default());
+            assert!(parse_res.is_err(), "not err; parse_res={:?}", parse_res);
+
+            let message = &parse_res.unwrap_err().to_string();
+            assert!(re.is_match(message), "message={message}");
+        }
+    }
+}
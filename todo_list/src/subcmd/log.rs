@@ -0,0 +1,69 @@
+use crate::args::{Opt, Spec};
+use crate::journal::JournaledStore;
+use crate::Error;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct LogOptions {
+    count: Option<usize>,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Prints the journaled operations, newest first, marking those undone.",
+    positionals: "",
+    options: &[Opt::value(
+        Some('n'),
+        "count",
+        "<count>",
+        "only the newest <count> operations",
+    )],
+};
+
+pub fn log_parser(args: impl Iterator<Item = String>) -> Result<LogOptions, Error> {
+    let matches = SPEC.parse(args)?;
+
+    Ok(LogOptions {
+        count: matches.parse_with("count", |x| {
+            x.parse().map_err(|_| format!("invalid count; {}", x))
+        })?,
+    })
+}
+
+pub fn log(options: LogOptions, store: &mut JournaledStore) -> Result<(), Error> {
+    let entries = store.entries()?;
+
+    for entry in entries
+        .iter()
+        .rev()
+        .take(options.count.unwrap_or(usize::MAX))
+    {
+        let mark = if entry.undone { " [undone]" } else { "" };
+        println!("{}{}", entry, mark);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::split_into_string_iter;
+    use rstest::*;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(std::iter::empty(), Ok(LogOptions { count: None }))]
+        #[case(split_into_string_iter("-n 5"), Ok(LogOptions { count: Some(5) }))]
+        #[case(
+            split_into_string_iter("-n x"),
+            Err(String::from("-n: invalid count; x"))
+        )]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: Result<LogOptions, String>,
+        ) {
+            assert_eq!(log_parser(args).map_err(|x| x.to_string()), expected);
+        }
+    }
+}
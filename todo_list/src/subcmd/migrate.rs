@@ -0,0 +1,113 @@
+use crate::args::{Opt, Spec};
+use crate::store::TodoStore;
+use crate::todo::SCHEMA_VERSION;
+use crate::Error;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MigrateOptions {
+    dry_run: bool,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Upgrades the stored todo list to the current file version.",
+    positionals: "",
+    options: &[Opt::flag(
+        Some('n'),
+        "dry-run",
+        "print what would change without changing anything",
+    )],
+};
+
+pub fn migrate_parser(args: impl Iterator<Item = String>) -> Result<MigrateOptions, Error> {
+    let matches = SPEC.parse(args)?;
+
+    Ok(MigrateOptions {
+        dry_run: matches.flag("dry-run"),
+    })
+}
+
+pub fn migrate(options: MigrateOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let _lock = store.lock()?;
+    let migrations = store.migrate(options.dry_run)?;
+
+    if migrations.is_empty() {
+        println!("already at version {}", SCHEMA_VERSION);
+        return Ok(());
+    }
+    for migration in &migrations {
+        println!("version {} -> {}", migration.from, migration.to);
+        for change in &migration.changes {
+            println!("  {}", change);
+        }
+    }
+
+    if options.dry_run {
+        println!("dry run; nothing changed");
+    } else {
+        println!("migrated to version {}", SCHEMA_VERSION);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::JsonStore;
+    use crate::test_utils::{copy_todo_list, split_into_string_iter};
+    use crate::todo::TodoList;
+    use regex::Regex;
+    use rstest::*;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(std::iter::empty(), MigrateOptions { dry_run: false })]
+        #[case(split_into_string_iter("--dry-run"), MigrateOptions { dry_run: true })]
+        #[case(split_into_string_iter("-n"), MigrateOptions { dry_run: true })]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: MigrateOptions,
+        ) {
+            assert_eq!(migrate_parser(args).unwrap(), expected);
+        }
+
+        #[test]
+        fn should_return_error_if_invalid_option_given() {
+            let re = Regex::new(r"invalid option; -x").unwrap();
+
+            let parse_res = migrate_parser(split_into_string_iter("-x"));
+            assert!(parse_res.is_err(), "not err; parse_res={:?}", parse_res);
+
+            let message = &parse_res.unwrap_err().to_string();
+            assert!(re.is_match(message), "message={message}");
+        }
+    }
+
+    mod migrate_tests {
+        use super::*;
+        use crate::migrate::version_of;
+        use std::fs;
+
+        fn read_version(path: &str) -> u32 {
+            version_of(&serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()).unwrap()
+        }
+
+        #[rstest]
+        #[case(true, 0)]
+        #[case(false, SCHEMA_VERSION)]
+        fn should_migrate_file_unless_dry_run(#[case] dry_run: bool, #[case] expected: u32) {
+            let (_dir, path) = copy_todo_list();
+
+            let res = migrate(MigrateOptions { dry_run }, &mut JsonStore::new(&path));
+            assert!(res.is_ok(), "not Ok; res={:?}", res);
+
+            assert_eq!(read_version(&path), expected);
+            assert_eq!(
+                TodoList::load(&path).unwrap(),
+                TodoList::load("test_assets/todo-list.json").unwrap()
+            );
+        }
+    }
+}
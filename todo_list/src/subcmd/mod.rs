@@ -1,9 +1,262 @@
 mod add;
+mod agenda;
+mod config;
+mod export;
+mod history;
+mod import;
+mod label;
+mod link;
+mod list;
+mod log;
+mod migrate;
+mod ready;
 mod rm;
+mod search;
+mod tree;
+mod undo;
+mod update;
 
-use add::AddOptions;
-use rm::RmOptions;
+use crate::args::{wants_help, Spec};
+use crate::config::Config;
+use crate::journal::JournaledStore;
+use crate::todo::Status;
+use crate::Error;
+use add::{add, add_parser, AddOptions};
+use agenda::{agenda, agenda_parser, AgendaOptions};
+use config::{config, config_parser, ConfigOptions};
+use export::{export, export_parser, ExportOptions};
+use history::{history, history_parser, HistoryOptions};
+use import::{import, import_parser, ImportOptions};
+use label::{label, label_parser, LabelOptions};
+use link::{link, link_parser, LinkOptions};
+use list::{list, list_parser, ListOptions};
+use log::{log, log_parser, LogOptions};
+use migrate::{migrate, migrate_parser, MigrateOptions};
+use ready::{ready, ready_parser, ReadyOptions};
+use rm::{rm, rm_parser, RmOptions};
+use search::{search, search_parser, SearchOptions};
+use tree::{tree, tree_parser, TreeOptions};
+use undo::{undo, undo_parser, UndoOptions};
+use update::{
+    transition, transition_parser, update, update_parser, TransitionOptions, UpdateOptions,
+};
+
+#[derive(Debug, PartialEq, Eq)]
 pub enum SubcmdOptions {
     Add(AddOptions),
     Rm(RmOptions),
+    Update(UpdateOptions),
+    Transition(TransitionOptions),
+    List(ListOptions),
+    Import(ImportOptions),
+    Export(ExportOptions),
+    Label(LabelOptions),
+    Agenda(AgendaOptions),
+    History(HistoryOptions),
+    Migrate(MigrateOptions),
+    Config(ConfigOptions),
+    Undo(UndoOptions),
+    Tree(TreeOptions),
+    Log(LogOptions),
+    Link(LinkOptions),
+    Ready(ReadyOptions),
+    Search(SearchOptions),
+    /// `--help` was given; holds the subcommand's help text.
+    Help(String),
+}
+
+fn spec(subcmd: &str) -> Option<&'static Spec> {
+    Some(match subcmd {
+        "add" => &add::SPEC,
+        "rm" => &rm::SPEC,
+        "update" | "edit" => &update::SPEC,
+        "start" | "done" => &update::TRANSITION_SPEC,
+        "list" => &list::SPEC,
+        "import" => &import::SPEC,
+        "export" => &export::SPEC,
+        "label" => &label::SPEC,
+        "agenda" | "overdue" => &agenda::SPEC,
+        "history" => &history::SPEC,
+        "migrate" => &migrate::SPEC,
+        "config" => &config::SPEC,
+        "undo" | "redo" => &undo::SPEC,
+        "log" => &log::SPEC,
+        "tree" => &tree::SPEC,
+        "link" => &link::SPEC,
+        "unlink" => &link::UNLINK_SPEC,
+        "ready" => &ready::SPEC,
+        "search" => &search::SPEC,
+        _ => return None,
+    })
+}
+
+/// The usage to show after `args` fail to parse: that of the subcommand they name, or `None`
+/// if they name no known subcommand.
+pub fn usage(args: &[String]) -> Option<String> {
+    let subcmd = args.first()?;
+    let spec = spec(subcmd)?;
+
+    Some(format!(
+        "{}\n\nsee todo_list {} --help",
+        spec.usage(subcmd),
+        subcmd
+    ))
+}
+
+/// `config` supplies the defaults for options not given on the command line.
+pub fn parse(
+    mut args: impl Iterator<Item = String>,
+    config: &Config,
+) -> Result<SubcmdOptions, Error> {
+    let subcmd = match args.next() {
+        Some(x) => x,
+        None => return Err(Error::usage("subcommand required")),
+    };
+    let args = args.collect::<Vec<_>>();
+    match spec(&subcmd) {
+        Some(spec) if wants_help(&args) => return Ok(SubcmdOptions::Help(spec.help(&subcmd))),
+        Some(_) => (),
+        None => return Err(Error::usage(format!("invalid subcommand; {}", subcmd))),
+    }
+
+    let args = args.into_iter();
+    match &subcmd[..] {
+        "add" => add_parser(args, config).map(SubcmdOptions::Add),
+        "rm" => rm_parser(args).map(SubcmdOptions::Rm),
+        "update" | "edit" => update_parser(args).map(SubcmdOptions::Update),
+        "start" => transition_parser(args, Status::InProgress).map(SubcmdOptions::Transition),
+        "done" => transition_parser(args, Status::Done).map(SubcmdOptions::Transition),
+        "list" => list_parser(args, config).map(SubcmdOptions::List),
+        "import" => import_parser(args).map(SubcmdOptions::Import),
+        "export" => export_parser(args).map(SubcmdOptions::Export),
+        "label" => label_parser(args).map(SubcmdOptions::Label),
+        "agenda" => agenda_parser(args, false, config).map(SubcmdOptions::Agenda),
+        "overdue" => agenda_parser(args, true, config).map(SubcmdOptions::Agenda),
+        "history" => history_parser(args).map(SubcmdOptions::History),
+        "migrate" => migrate_parser(args).map(SubcmdOptions::Migrate),
+        "config" => config_parser(args, config).map(SubcmdOptions::Config),
+        "undo" => undo_parser(args, false).map(SubcmdOptions::Undo),
+        "redo" => undo_parser(args, true).map(SubcmdOptions::Undo),
+        "log" => log_parser(args).map(SubcmdOptions::Log),
+        "tree" => tree_parser(args, config).map(SubcmdOptions::Tree),
+        "link" => link_parser(args, false).map(SubcmdOptions::Link),
+        "unlink" => link_parser(args, true).map(SubcmdOptions::Link),
+        "ready" => ready_parser(args, config).map(SubcmdOptions::Ready),
+        "search" => search_parser(args, config).map(SubcmdOptions::Search),
+        _ => unreachable!("no spec for {}", subcmd),
+    }
+}
+
+pub fn exec(options: SubcmdOptions, store: &mut JournaledStore) -> Result<(), Error> {
+    match options {
+        SubcmdOptions::Add(options) => add(options, store),
+        SubcmdOptions::Rm(options) => rm(options, store),
+        SubcmdOptions::Update(options) => update(options, store),
+        SubcmdOptions::Transition(options) => transition(options, store),
+        SubcmdOptions::List(options) => list(options, store),
+        SubcmdOptions::Import(options) => import(options, store),
+        SubcmdOptions::Export(options) => export(options, store),
+        SubcmdOptions::Label(options) => label(options, store),
+        SubcmdOptions::Agenda(options) => agenda(options, store),
+        SubcmdOptions::History(options) => history(options, store),
+        SubcmdOptions::Migrate(options) => migrate(options, store),
+        SubcmdOptions::Config(options) => config(options, store),
+        SubcmdOptions::Undo(options) => undo(options, store),
+        SubcmdOptions::Log(options) => log(options, store),
+        SubcmdOptions::Tree(options) => tree(options, store),
+        SubcmdOptions::Link(options) => link(options, store),
+        SubcmdOptions::Ready(options) => ready(options, store),
+        SubcmdOptions::Search(options) => search(options, store),
+        SubcmdOptions::Help(help) => {
+            println!("{}", help);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::split_into_string_iter;
+    use regex::Regex;
+    use rstest::*;
+
+    mod parse_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(split_into_string_iter("list"))]
+        #[case(split_into_string_iter("list --sort title"))]
+        fn should_parse_list_subcmd(#[case] args: impl Iterator<Item = String>) {
+            let parse_res = parse(args, &Config::default());
+            assert!(
+                matches!(parse_res, Ok(SubcmdOptions::List(_))),
+                "not list; parse_res={:?}",
+                parse_res
+            );
+        }
+
+        #[rstest]
+        #[case(
+            split_into_string_iter("add --help"),
+            "usage: todo_list add -t <title>"
+        )]
+        #[case(
+            split_into_string_iter("done -h 1"),
+            "usage: todo_list done <id|hash>..."
+        )]
+        #[case(
+            split_into_string_iter("edit -x --help"),
+            "usage: todo_list edit [-t <title>]"
+        )]
+        fn should_return_help_if_requested(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] prefix: &str,
+        ) {
+            match parse(args, &Config::default()) {
+                Ok(SubcmdOptions::Help(help)) => {
+                    assert!(help.starts_with(prefix), "help={help}")
+                }
+                x => panic!("not help; parse_res={:?}", x),
+            }
+        }
+
+        #[rstest]
+        #[case(split_into_string_iter("help --help"), r"invalid subcommand")]
+        #[case(split_into_string_iter("invalid"), r"invalid subcommand")]
+        #[case(split_into_string_iter("list -x"), r"invalid option")]
+        #[case(std::iter::empty(), r"subcommand required")]
+        fn should_return_error_if_invalid_args_given(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] pattern: &str,
+        ) {
+            let re = Regex::new(pattern).unwrap();
+
+            let parse_res = parse(args, &Config::default());
+            assert!(parse_res.is_err(), "not err; parse_res={:?}", parse_res);
+
+            let message = &parse_res.unwrap_err().to_string();
+            assert!(re.is_match(message), "message={message}");
+        }
+
+        #[rstest]
+        #[case("list --until x", Some("usage: todo_list list [--where <query>]"))]
+        #[case("done", Some("usage: todo_list done <id|hash>..."))]
+        #[case("invalid", None)]
+        #[case("", None)]
+        fn should_give_usage_of_failing_subcmd(
+            #[case] args: &'static str,
+            #[case] prefix: Option<&str>,
+        ) {
+            let args = split_into_string_iter(args).collect::<Vec<_>>();
+
+            match (usage(&args), prefix) {
+                (Some(usage), Some(prefix)) => {
+                    assert!(usage.starts_with(prefix), "usage={usage}");
+                    assert!(usage.ends_with(&format!("see todo_list {} --help", args[0])));
+                }
+                (usage, prefix) => assert_eq!(usage.is_none(), prefix.is_none()),
+            }
+        }
+    }
 }
@@ -0,0 +1,72 @@
+use crate::args::{Opt, Spec};
+use crate::config::Config;
+use crate::store::TodoStore;
+use crate::table::{display, Column};
+use crate::Error;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReadyOptions {
+    wrap: bool,
+    columns: Vec<Column>,
+    date_format: String,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Prints ToDo todos whose blockers are all Done, each after the todos it waited on.",
+    positionals: "",
+    options: &[Opt::flag(
+        Some('w'),
+        "wrap",
+        "wrap long values instead of truncating them",
+    )],
+};
+
+pub fn ready_parser(
+    args: impl Iterator<Item = String>,
+    config: &Config,
+) -> Result<ReadyOptions, Error> {
+    let matches = SPEC.parse(args)?;
+
+    Ok(ReadyOptions {
+        wrap: matches.flag("wrap"),
+        columns: config.columns(),
+        date_format: config.date_format(),
+    })
+}
+
+pub fn ready(options: ReadyOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let todo_list = store.load()?;
+    display(
+        todo_list.ready().into_iter(),
+        &options.columns,
+        options.wrap,
+        &options.date_format,
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::split_into_string_iter;
+    use rstest::*;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(std::iter::empty(), false)]
+        #[case(split_into_string_iter("-w"), true)]
+        fn should_parse_args(#[case] args: impl Iterator<Item = String>, #[case] wrap: bool) {
+            assert_eq!(ready_parser(args, &Config::default()).unwrap().wrap, wrap);
+        }
+
+        #[test]
+        fn should_return_error_if_extra_argument_given() {
+            let parse_res = ready_parser(split_into_string_iter("1"), &Config::default());
+
+            assert_eq!(parse_res.unwrap_err().to_string(), "unexpected argument; 1");
+        }
+    }
+}
@@ -1,28 +1,286 @@
+use crate::args::{parse_id, Opt, Spec};
+use crate::filter::Filter;
+use crate::store::TodoStore;
+use crate::todo::{ChildPolicy, Status, Todo};
+use crate::Error;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::RangeInclusive;
+
+/// `ids` are numeric ids or hash id prefixes, resolved once the list is loaded.
 #[derive(Debug, PartialEq, Eq)]
 pub struct RmOptions {
-    id: i32,
+    ids: Vec<String>,
+    /// Select the todos whose id falls in them; unlike `ids`, gaps are not reported.
+    ranges: Vec<RangeInclusive<usize>>,
+    filter: Option<Filter>,
+    status: Option<Status>,
+    label: Option<String>,
+    children: ChildPolicy,
 }
 
-fn rm_parser(mut args: impl Iterator<Item = String>) -> Result<RmOptions, String> {
-    let mut id: Option<String> = None;
+impl RmOptions {
+    fn matches(&self, todo: &Todo) -> bool {
+        self.filter.as_ref().is_none_or(|x| x.matches(todo))
+            && self.status.as_ref().is_none_or(|x| &todo.status == x)
+            && self.label.as_ref().is_none_or(|x| todo.labels.contains(x))
+    }
+}
 
-    // while let Some(option) = args.next() {
-    //     while &option[..] {
-    //         ""
-    //     }
-    // }
+/// Parses `<from>-<to>`; `None` if `arg` is a single id.
+fn parse_range(arg: &str) -> Result<Option<RangeInclusive<usize>>, Error> {
+    let Some((start, end)) = arg.split_once('-') else {
+        return Ok(None);
+    };
+    match (parse_id(start), parse_id(end)) {
+        (Ok(start), Ok(end)) if start <= end => Ok(Some(start..=end)),
+        _ => Err(Error::usage(format!("invalid range; {}", arg))),
+    }
+}
 
-    Err("".to_string())
+pub const SPEC: Spec = Spec {
+    about: "Removes the given todos, or every todo matching the filters, and prints their ids.",
+    positionals: "<id|hash|from-to>...",
+    options: &[
+        Opt::value(None, "where", "<query>", "only todos matching the query"),
+        Opt::value(
+            Some('s'),
+            "status",
+            "<status>",
+            "only todos with this status",
+        ),
+        Opt::value(Some('l'), "label", "<label>", "only todos with this label"),
+        Opt::value(
+            None,
+            "children",
+            "refuse|cascade|reparent",
+            "what to do with subtasks of removed todos; refuse by default",
+        ),
+    ],
+};
+
+pub fn rm_parser(args: impl Iterator<Item = String>) -> Result<RmOptions, Error> {
+    let matches = SPEC.parse(args)?;
+    let mut ids = Vec::new();
+    let mut ranges = Vec::new();
+    for arg in matches.positionals() {
+        match parse_range(arg)? {
+            Some(range) => ranges.push(range),
+            None => ids.push(arg.clone()),
+        }
+    }
+    let filter = matches.parse("where")?;
+    let status = matches.parse("status")?;
+    let label = matches.value("label").map(String::from);
+    let children = matches.parse("children")?.unwrap_or_default();
+
+    if ids.is_empty()
+        && ranges.is_empty()
+        && filter.is_none()
+        && status.is_none()
+        && label.is_none()
+    {
+        return Err(Error::usage("id or filter option required"));
+    }
+
+    Ok(RmOptions {
+        ids,
+        ranges,
+        filter,
+        status,
+        label,
+        children,
+    })
+}
+
+pub fn rm(options: RmOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let _lock = store.lock()?;
+    let mut todo_list = store.load()?;
+    let mut not_found = Vec::new();
+
+    let ids = if options.ids.is_empty() && options.ranges.is_empty() {
+        todo_list
+            .list()
+            .iter()
+            .filter(|x| options.matches(x))
+            .map(|x| x.id)
+            .collect::<Vec<_>>()
+    } else {
+        let todos = todo_list
+            .list()
+            .iter()
+            .map(|x| (x.id, x))
+            .collect::<HashMap<_, _>>();
+        let in_range = todo_list
+            .list()
+            .iter()
+            .map(|x| x.id)
+            .filter(|id| options.ranges.iter().any(|x| x.contains(id)));
+        let mut ids = Vec::new();
+        for x in &options.ids {
+            match todo_list.resolve(x) {
+                Ok(id) => ids.push(id),
+                Err(Error::NotFound(x)) => not_found.push(x),
+                Err(e) => return Err(e),
+            }
+        }
+        ids.into_iter()
+            .chain(in_range)
+            .filter(|id| todos.get(id).is_none_or(|x| options.matches(x)))
+            .collect::<Vec<_>>()
+    };
+
+    let removal = todo_list.remove_with(&ids, options.children)?;
+    store.delete(&todo_list, &removal.removed)?;
+    let changed = removal
+        .reparented
+        .iter()
+        .chain(&removal.unlinked)
+        .copied()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    if !changed.is_empty() {
+        store.update(&todo_list, &changed)?;
+    }
+
+    for id in removal.removed {
+        println!("{}", id);
+    }
+    not_found.extend(removal.not_found.iter().map(|x| x.to_string()));
+    if !not_found.is_empty() {
+        eprintln!("not found: {}", not_found.join(", "));
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::split_into_string_iter;
+    use crate::store::JsonStore;
+    use crate::test_utils::{copy_todo_list, split_into_string_iter};
+    use crate::todo::TodoList;
     use regex::Regex;
     use rstest::*;
 
     mod parse_args_tests {
         use super::*;
+
+        #[rstest]
+        #[case(
+            split_into_string_iter("1"),
+            RmOptions { ids: vec![String::from("1")], ranges: vec![], filter: None, status: None, label: None, children: ChildPolicy::Refuse }
+        )]
+        #[case(
+            split_into_string_iter("1 3-5 8 5-99999999999"),
+            RmOptions {
+                ids: vec![String::from("1"), String::from("8")],
+                ranges: vec![3..=5, 5..=99999999999],
+                filter: None,
+                status: None,
+                label: None,
+                children: ChildPolicy::Refuse,
+            }
+        )]
+        #[case(
+            split_into_string_iter("--status Done"),
+            RmOptions { ids: vec![], ranges: vec![], filter: None, status: Some(Status::Done), label: None, children: ChildPolicy::Refuse }
+        )]
+        #[case(
+            split_into_string_iter("0-2 -s ToDo -l label1"),
+            RmOptions {
+                ids: vec![],
+                ranges: vec![0..=2],
+                filter: None,
+                status: Some(Status::ToDo),
+                label: Some("label1".to_string()),
+                children: ChildPolicy::Refuse,
+            }
+        )]
+        #[case(
+            split_into_string_iter("1 --children cascade"),
+            RmOptions { ids: vec![String::from("1")], ranges: vec![], filter: None, status: None, label: None, children: ChildPolicy::Cascade }
+        )]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: RmOptions,
+        ) {
+            assert_eq!(rm_parser(args).unwrap(), expected);
+        }
+
+        #[rstest]
+        #[case(std::iter::empty(), r"id or filter option required")]
+        #[case(split_into_string_iter("3-x"), r"invalid range; 3-x")]
+        #[case(split_into_string_iter("7-3"), r"invalid range; 7-3")]
+        #[case(split_into_string_iter("1 -x"), r"invalid option; -x")]
+        #[case(split_into_string_iter("--status Doing"), r"invalid status; Doing")]
+        #[case(split_into_string_iter("--label"), r"--label: option requires a value")]
+        #[case(
+            split_into_string_iter("1 --children keep"),
+            r"--children: invalid child policy; keep"
+        )]
+        fn should_return_error_if_invalid_args_given(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] pattern: &str,
+        ) {
+            let re = Regex::new(pattern).unwrap();
+
+            let parse_res = rm_parser(args);
+            assert!(parse_res.is_err(), "not err; parse_res={:?}", parse_res);
+
+            let message = &parse_res.unwrap_err().to_string();
+            assert!(re.is_match(message), "message={message}");
+        }
+    }
+
+    mod rm_tests {
+        use super::*;
+
+        fn remaining_ids(path: &str) -> Vec<usize> {
+            TodoList::load(path)
+                .unwrap()
+                .list()
+                .iter()
+                .map(|x| x.id)
+                .collect()
+        }
+
+        #[rstest]
+        #[case(split_into_string_iter("1 5"), vec![0, 2])]
+        #[case(split_into_string_iter("0-2"), vec![])]
+        #[case(split_into_string_iter("1-100000 0"), vec![])]
+        #[case(split_into_string_iter("--status Done"), vec![0, 1])]
+        #[case(split_into_string_iter("--label label1"), vec![0, 2])]
+        #[case(split_into_string_iter("0-1 --status ToDo"), vec![1, 2])]
+        #[case(
+            ["--where", "status:Done OR label:label1"].into_iter().map(String::from),
+            vec![0]
+        )]
+        #[case(split_into_string_iter("0-1 --where id>0"), vec![0, 2])]
+        fn should_remove_todos(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: Vec<usize>,
+        ) {
+            let (_dir, path) = copy_todo_list();
+
+            let res = rm(rm_parser(args).unwrap(), &mut JsonStore::new(&path));
+            assert!(res.is_ok(), "not Ok; res={:?}", res);
+
+            assert_eq!(remaining_ids(&path), expected);
+        }
+
+        #[test]
+        fn should_remove_todo_by_hash_id() {
+            let (_dir, path) = copy_todo_list();
+            let hash = TodoList::load(&path).unwrap().get(1).unwrap().hash_id();
+
+            let res = rm(
+                rm_parser([hash[..7].to_string()].into_iter()).unwrap(),
+                &mut JsonStore::new(&path),
+            );
+            assert!(res.is_ok(), "not Ok; res={:?}", res);
+
+            assert_eq!(remaining_ids(&path), vec![0, 2]);
+        }
     }
 }
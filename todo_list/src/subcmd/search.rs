@@ -0,0 +1,108 @@
+use crate::args::{Opt, Spec};
+use crate::config::Config;
+use crate::search::{tokenize, Index};
+use crate::store::TodoStore;
+use crate::table::{display_hits, Column};
+use crate::Error;
+
+/// `columns` starts with [`Column::Score`], followed by the configured columns.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SearchOptions {
+    query: String,
+    count: Option<usize>,
+    wrap: bool,
+    columns: Vec<Column>,
+    date_format: String,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Prints todos containing every given word, or a word starting with it, in their \
+            title, description or labels, most relevant first. Title matches weigh most, \
+            then labels, then descriptions; case is ignored.",
+    positionals: "<word>...",
+    options: &[
+        Opt::value(Some('n'), "count", "<count>", "only the best <count> todos"),
+        Opt::flag(
+            Some('w'),
+            "wrap",
+            "wrap long values instead of truncating them",
+        ),
+    ],
+};
+
+pub fn search_parser(
+    args: impl Iterator<Item = String>,
+    config: &Config,
+) -> Result<SearchOptions, Error> {
+    let matches = SPEC.parse(args)?;
+    let query = matches.positionals().join(" ");
+    if tokenize(&query).next().is_none() {
+        return Err(Error::usage("search word required"));
+    }
+
+    Ok(SearchOptions {
+        query,
+        count: matches.parse_with("count", |x| {
+            x.parse().map_err(|_| format!("invalid count; {}", x))
+        })?,
+        wrap: matches.flag("wrap"),
+        columns: [Column::Score]
+            .into_iter()
+            .chain(config.columns())
+            .collect(),
+        date_format: config.date_format(),
+    })
+}
+
+pub fn search(options: SearchOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let todo_list = store.load()?;
+    let index = Index::new(todo_list.list().iter());
+    let mut hits = index.search(&options.query);
+    hits.truncate(options.count.unwrap_or(usize::MAX));
+
+    display_hits(&hits, &options.columns, options.wrap, &options.date_format);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::split_into_string_iter;
+    use rstest::*;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(split_into_string_iter("rotate notes"), "rotate notes", None)]
+        #[case(split_into_string_iter("-n 3 on-call"), "on-call", Some(3))]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] query: &str,
+            #[case] count: Option<usize>,
+        ) {
+            let options = search_parser(args, &Config::default()).unwrap();
+
+            assert_eq!((&options.query[..], options.count), (query, count));
+            assert_eq!(options.columns[0], Column::Score);
+        }
+
+        #[rstest]
+        #[case(std::iter::empty(), "search word required")]
+        #[case(split_into_string_iter("--"), "search word required")]
+        #[case(split_into_string_iter("-- !?"), "search word required")]
+        #[case(split_into_string_iter("-n x notes"), "-n: invalid count; x")]
+        fn should_return_error_if_invalid_args_given(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: &str,
+        ) {
+            assert_eq!(
+                search_parser(args, &Config::default())
+                    .unwrap_err()
+                    .to_string(),
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::args::{Opt, Spec};
+use crate::config::Config;
+use crate::store::TodoStore;
+use crate::table::{display_tree, Column, Node};
+use crate::Error;
+
+/// `root` is a numeric id or a hash id prefix, resolved once the list is loaded.
+/// `columns` has [`Column::Progress`] right after the title.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TreeOptions {
+    root: Option<String>,
+    wrap: bool,
+    columns: Vec<Column>,
+    date_format: String,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Prints todos with their subtasks indented beneath them and each parent's count \
+            of done subtasks; with an id, only that todo and its subtasks.",
+    positionals: "[<id|hash>]",
+    options: &[Opt::flag(
+        Some('w'),
+        "wrap",
+        "wrap long values instead of truncating them",
+    )],
+};
+
+pub fn tree_parser(
+    args: impl Iterator<Item = String>,
+    config: &Config,
+) -> Result<TreeOptions, Error> {
+    let matches = SPEC.parse(args)?;
+    let root = match matches.positionals() {
+        [] => None,
+        [id] => Some(id.clone()),
+        [_, x, ..] => return Err(Error::usage(format!("unexpected argument; {}", x))),
+    };
+
+    Ok(TreeOptions {
+        root,
+        wrap: matches.flag("wrap"),
+        columns: with_progress(config.columns()),
+        date_format: config.date_format(),
+    })
+}
+
+fn with_progress(mut columns: Vec<Column>) -> Vec<Column> {
+    let at = columns
+        .iter()
+        .position(|x| *x == Column::Title)
+        .map_or(columns.len(), |i| i + 1);
+    columns.insert(at, Column::Progress);
+    columns
+}
+
+pub fn tree(options: TreeOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let todo_list = store.load()?;
+    let root = options.root.map(|x| todo_list.resolve(&x)).transpose()?;
+
+    let nodes = todo_list
+        .tree(root)
+        .into_iter()
+        .map(|(depth, todo)| Node {
+            todo,
+            depth,
+            progress: todo_list.progress(todo.id),
+        })
+        .collect::<Vec<_>>();
+    display_tree(&nodes, &options.columns, options.wrap, &options.date_format);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::split_into_string_iter;
+    use rstest::*;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(std::iter::empty(), None, false)]
+        #[case(split_into_string_iter("3 --wrap"), Some("3"), true)]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] root: Option<&str>,
+            #[case] wrap: bool,
+        ) {
+            let options = tree_parser(args, &Config::default()).unwrap();
+
+            assert_eq!(options.root.as_deref(), root);
+            assert_eq!(options.wrap, wrap);
+            assert_eq!(options.columns[2], Column::Progress);
+        }
+
+        #[test]
+        fn should_return_error_if_extra_argument_given() {
+            let parse_res = tree_parser(split_into_string_iter("1 2"), &Config::default());
+
+            assert_eq!(parse_res.unwrap_err().to_string(), "unexpected argument; 2");
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use crate::args::Spec;
+use crate::journal::JournaledStore;
+use crate::Error;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UndoOptions {
+    count: usize,
+    redo: bool,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Reverts the last operations that changed the list, newest first; redo reapplies \
+            undone ones until another change is made.",
+    positionals: "[<count>]",
+    options: &[],
+};
+
+/// Parses the optional count given to `undo`, or to `redo` when `redo` is set.
+pub fn undo_parser(args: impl Iterator<Item = String>, redo: bool) -> Result<UndoOptions, Error> {
+    let count = match SPEC.parse(args)?.positionals() {
+        [] => 1,
+        [x] => match x.parse() {
+            Ok(count) if count > 0 => count,
+            _ => return Err(Error::usage(format!("invalid count; {}", x))),
+        },
+        [_, x, ..] => return Err(Error::usage(format!("unexpected argument; {}", x))),
+    };
+
+    Ok(UndoOptions { count, redo })
+}
+
+pub fn undo(options: UndoOptions, store: &mut JournaledStore) -> Result<(), Error> {
+    let (verb, entries) = if options.redo {
+        ("redid", store.redo(options.count)?)
+    } else {
+        ("undid", store.undo(options.count)?)
+    };
+
+    for entry in &entries {
+        println!("{} {}", verb, entry);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::split_into_string_iter;
+    use rstest::*;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(std::iter::empty(), false, Ok(UndoOptions { count: 1, redo: false }))]
+        #[case(split_into_string_iter("3"), true, Ok(UndoOptions { count: 3, redo: true }))]
+        #[case(
+            split_into_string_iter("0"),
+            false,
+            Err(String::from("invalid count; 0"))
+        )]
+        #[case(
+            split_into_string_iter("x"),
+            false,
+            Err(String::from("invalid count; x"))
+        )]
+        #[case(
+            split_into_string_iter("1 2"),
+            false,
+            Err(String::from("unexpected argument; 2"))
+        )]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] redo: bool,
+            #[case] expected: Result<UndoOptions, String>,
+        ) {
+            assert_eq!(undo_parser(args, redo).map_err(|x| x.to_string()), expected);
+        }
+    }
+}
@@ -0,0 +1,478 @@
+use crate::args::{parse_id, Opt, Spec};
+use crate::date::{parse_date, today, DATE_FORMAT};
+use crate::filter::Filter;
+use crate::store::TodoStore;
+use crate::todo::{split_labels, Status, TodoList, TodoPatch};
+use crate::Error;
+
+/// The todos an update applies to: one by numeric id or hash id prefix, resolved once the
+/// list is loaded, or every todo matching a query.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Target {
+    Id(String),
+    Where(Filter),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UpdateOptions {
+    target: Target,
+    patch: TodoPatch,
+}
+
+/// `ids` are numeric ids or hash id prefixes, resolved once the list is loaded.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TransitionOptions {
+    ids: Vec<String>,
+    status: Status,
+}
+
+pub const SPEC: Spec = Spec {
+    about: "Changes the given fields of a todo, or of every todo matching --where, \
+            and prints their ids.",
+    positionals: "<id|hash>",
+    options: &[
+        Opt::value(Some('t'), "title", "<title>", "new title"),
+        Opt::value(Some('d'), "description", "<description>", "new description"),
+        Opt::value(
+            Some('l'),
+            "label",
+            "<label>[,<label>...]",
+            "labels replacing the current ones",
+        )
+        .repeated(),
+        Opt::value(Some('s'), "status", "<status>", "new status"),
+        Opt::value(
+            None,
+            "due",
+            "<date>|none",
+            "new due date, or none to clear it",
+        ),
+        Opt::value(
+            Some('p'),
+            "priority",
+            "low|normal|high|urgent",
+            "new priority",
+        ),
+        Opt::value(
+            None,
+            "parent",
+            "<id>|none",
+            "todo to make it a subtask of, or none to move it to the top level",
+        ),
+        Opt::value(
+            None,
+            "repeat",
+            "<rule>|none",
+            "new recurrence as for add, or none to stop repeating",
+        ),
+        Opt::value(
+            None,
+            "where",
+            "<query>",
+            "update every todo matching the query instead of one id",
+        ),
+    ],
+};
+
+pub fn update_parser(args: impl Iterator<Item = String>) -> Result<UpdateOptions, Error> {
+    let matches = SPEC.parse(args)?;
+    let target = match (matches.positionals(), matches.parse("where")?) {
+        ([], None) => return Err(Error::usage("id required unless --where is given")),
+        ([], Some(filter)) => Target::Where(filter),
+        ([id], None) => Target::Id(id.clone()),
+        ([x, ..], Some(_)) | ([_, x, ..], None) => {
+            return Err(Error::usage(format!("unexpected argument; {}", x)))
+        }
+    };
+
+    let patch = TodoPatch {
+        title: matches.value("title").map(String::from),
+        description: matches.value("description").map(String::from),
+        labels: matches
+            .flag("label")
+            .then(|| matches.values("label").flat_map(split_labels).collect()),
+        status: matches.parse("status")?,
+        due: matches.parse_with("due", |x| match x {
+            "none" => Ok(None),
+            x => parse_date(x, today()).map(Some),
+        })?,
+        priority: matches.parse("priority")?,
+        parent: matches.parse_with("parent", |x| match x {
+            "none" => Ok(None),
+            x => parse_id(x).map(Some),
+        })?,
+        recurrence: matches.parse_with("repeat", |x| match x {
+            "none" => Ok(None),
+            x => x.parse().map(Some),
+        })?,
+    };
+
+    if patch == TodoPatch::default() {
+        return Err(Error::usage(
+            "at least one of -t, -d, -l, -s, --due, -p, --parent or --repeat option required",
+        ));
+    }
+
+    Ok(UpdateOptions { target, patch })
+}
+
+/// Applies `patch` to todo `id` and returns the id of the next occurrence if completing
+/// a recurring todo added one.
+fn apply(todo_list: &mut TodoList, id: usize, patch: &TodoPatch) -> Result<Option<usize>, Error> {
+    let next = todo_list.get(id).and_then(|x| x.next);
+    let todo = todo_list.update(id, patch)?;
+
+    Ok(todo.next.filter(|&x| Some(x) != next))
+}
+
+fn report_next(todo_list: &TodoList, next: &[usize]) {
+    for todo in next.iter().filter_map(|&id| todo_list.get(id)) {
+        let due = todo.due.map(|x| x.format(DATE_FORMAT).to_string());
+        eprintln!(
+            "next occurrence: {} due {}",
+            todo.id,
+            due.unwrap_or_default()
+        );
+    }
+}
+
+pub fn update(options: UpdateOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let _lock = store.lock()?;
+    let mut todo_list = store.load()?;
+    let ids = match &options.target {
+        Target::Id(id) => vec![todo_list.resolve(id)?],
+        Target::Where(filter) => todo_list
+            .list()
+            .iter()
+            .filter(|x| filter.matches(x))
+            .map(|x| x.id)
+            .collect(),
+    };
+
+    let mut next = Vec::new();
+    for &id in &ids {
+        next.extend(apply(&mut todo_list, id, &options.patch)?);
+    }
+    if !ids.is_empty() {
+        store.update(&todo_list, &ids)?;
+    }
+    if !next.is_empty() {
+        store.insert(&todo_list, &next)?;
+    }
+
+    for id in ids {
+        println!("{}", id);
+    }
+    report_next(&todo_list, &next);
+
+    Ok(())
+}
+
+pub const TRANSITION_SPEC: Spec = Spec {
+    about: "Moves todos forward: start marks them InProgress, done marks them Done.",
+    positionals: "<id|hash>...",
+    options: &[],
+};
+
+/// Parses the ids given to the `start`/`done` shortcuts, which move todos forward to `status`.
+pub fn transition_parser(
+    args: impl Iterator<Item = String>,
+    status: Status,
+) -> Result<TransitionOptions, Error> {
+    let ids = TRANSITION_SPEC.parse(args)?.positionals().to_vec();
+
+    if ids.is_empty() {
+        return Err(Error::usage("id required"));
+    }
+
+    Ok(TransitionOptions { ids, status })
+}
+
+pub fn transition(options: TransitionOptions, store: &mut dyn TodoStore) -> Result<(), Error> {
+    let _lock = store.lock()?;
+    let mut todo_list = store.load()?;
+    let ids = options
+        .ids
+        .iter()
+        .map(|x| todo_list.resolve(x))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for &id in &ids {
+        if let Some(todo) = todo_list.get(id).filter(|x| x.status >= options.status) {
+            return Err(Error::Conflict(format!(
+                "todo {} is already {}",
+                id, todo.status
+            )));
+        }
+    }
+
+    let patch = TodoPatch {
+        status: Some(options.status),
+        ..Default::default()
+    };
+    let mut next = Vec::new();
+    for &id in &ids {
+        next.extend(apply(&mut todo_list, id, &patch)?);
+    }
+    store.update(&todo_list, &ids)?;
+    if !next.is_empty() {
+        store.insert(&todo_list, &next)?;
+    }
+
+    for id in ids {
+        println!("{}", id);
+    }
+    report_next(&todo_list, &next);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::JsonStore;
+    use crate::test_utils::{copy_todo_list, labels, split_into_string_iter};
+    use crate::todo::Priority;
+    use chrono::NaiveDate;
+    use regex::Regex;
+    use rstest::*;
+
+    mod parse_args_tests {
+        use super::*;
+
+        #[rstest]
+        #[case(
+            split_into_string_iter("1 -t title1 -d description1 -l label1 -s Done"),
+            UpdateOptions {
+                target: Target::Id(String::from("1")),
+                patch: TodoPatch {
+                    title: Some("title1".to_string()),
+                    description: Some("description1".to_string()),
+                    labels: Some(labels(&["label1"])),
+                    status: Some(Status::Done),
+                    ..Default::default()
+                },
+            }
+        )]
+        #[case(
+            split_into_string_iter("0 --label label0 -l label1"),
+            UpdateOptions {
+                target: Target::Id(String::from("0")),
+                patch: TodoPatch { labels: Some(labels(&["label0", "label1"])), ..Default::default() },
+            }
+        )]
+        #[case(
+            split_into_string_iter("0 --due none -p urgent"),
+            UpdateOptions {
+                target: Target::Id(String::from("0")),
+                patch: TodoPatch {
+                    due: Some(None),
+                    priority: Some(Priority::Urgent),
+                    ..Default::default()
+                },
+            }
+        )]
+        #[case(
+            split_into_string_iter("0 --due 2026-11-01"),
+            UpdateOptions {
+                target: Target::Id(String::from("0")),
+                patch: TodoPatch { due: NaiveDate::from_ymd_opt(2026, 11, 1).map(Some), ..Default::default() },
+            }
+        )]
+        fn should_parse_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: UpdateOptions,
+        ) {
+            assert_eq!(update_parser(args).unwrap(), expected);
+        }
+
+        #[rstest]
+        #[case(std::iter::empty(), r"id required")]
+        #[case(split_into_string_iter("1"), r"at least one of")]
+        #[case(split_into_string_iter("1 -t"), r"-t: option requires a value")]
+        #[case(split_into_string_iter("1 -s Doing"), r"invalid status; Doing")]
+        #[case(
+            split_into_string_iter("1 --where id:1 -p high"),
+            r"unexpected argument; 1"
+        )]
+        #[case(
+            split_into_string_iter("--where id= -p high"),
+            r"expected a value after `id=`"
+        )]
+        #[case(split_into_string_iter("1 --repeat 0d"), r"invalid recurrence; 0d")]
+        #[case(split_into_string_iter("1 -x x"), r"invalid option; -x")]
+        fn should_return_error_if_invalid_args_given(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] pattern: &str,
+        ) {
+            let re = Regex::new(pattern).unwrap();
+
+            let parse_res = update_parser(args);
+            assert!(parse_res.is_err(), "not err; parse_res={:?}", parse_res);
+
+            let message = &parse_res.unwrap_err().to_string();
+            assert!(re.is_match(message), "message={message}");
+        }
+
+        #[rstest]
+        #[case(
+            split_into_string_iter("0 2b1"),
+            Ok(TransitionOptions { ids: vec![String::from("0"), String::from("2b1")], status: Status::Done })
+        )]
+        #[case(std::iter::empty(), Err(String::from("id required")))]
+        fn should_parse_transition_args(
+            #[case] args: impl Iterator<Item = String>,
+            #[case] expected: Result<TransitionOptions, String>,
+        ) {
+            let parse_res = transition_parser(args, Status::Done).map_err(|x| x.to_string());
+            assert_eq!(parse_res, expected);
+        }
+    }
+
+    mod update_tests {
+        use super::*;
+
+        #[test]
+        fn should_update_todo() {
+            let (_dir, path) = copy_todo_list();
+
+            let res = update(
+                update_parser(split_into_string_iter("0 -t updated -s InProgress")).unwrap(),
+                &mut JsonStore::new(&path),
+            );
+            assert!(res.is_ok(), "not Ok; res={:?}", res);
+
+            let todo_list = TodoList::load(&path).unwrap();
+            let todo = todo_list.get(0).unwrap();
+            assert_eq!(todo.title, "updated");
+            assert_eq!(todo.status, Status::InProgress);
+        }
+
+        #[test]
+        fn should_update_every_todo_matching_query() {
+            let (_dir, path) = copy_todo_list();
+            let args = ["--where", "status!=Done", "-p", "urgent"]
+                .into_iter()
+                .map(String::from);
+
+            let res = update(update_parser(args).unwrap(), &mut JsonStore::new(&path));
+            assert!(res.is_ok(), "not Ok; res={:?}", res);
+
+            let todo_list = TodoList::load(&path).unwrap();
+            assert_eq!(
+                todo_list
+                    .list()
+                    .iter()
+                    .map(|x| (x.id, x.priority))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (0, Priority::Urgent),
+                    (1, Priority::Urgent),
+                    (2, Priority::Normal)
+                ]
+            );
+        }
+
+        #[test]
+        fn should_update_todo_by_hash_id() {
+            let (_dir, path) = copy_todo_list();
+            let hash = TodoList::load(&path).unwrap().get(2).unwrap().hash_id();
+
+            let res = update(
+                update_parser(
+                    [
+                        hash[..7].to_string(),
+                        String::from("-t"),
+                        String::from("updated"),
+                    ]
+                    .into_iter(),
+                )
+                .unwrap(),
+                &mut JsonStore::new(&path),
+            );
+            assert!(res.is_ok(), "not Ok; res={:?}", res);
+
+            let todo_list = TodoList::load(&path).unwrap();
+            assert_eq!(todo_list.get(2).unwrap().title, "updated");
+        }
+
+        #[rstest]
+        #[case("9")]
+        #[case("x")]
+        fn should_return_error_if_id_not_found(#[case] id: &str) {
+            let (_dir, path) = copy_todo_list();
+            let args = [id, "-t", "updated"].into_iter().map(String::from);
+
+            let res = update(update_parser(args).unwrap(), &mut JsonStore::new(&path));
+            assert!(res.is_err(), "not err; res={:?}", res);
+        }
+
+        #[rstest]
+        #[case(Status::InProgress, "0", Status::InProgress)]
+        #[case(Status::Done, "0", Status::Done)]
+        #[case(Status::Done, "1", Status::Done)]
+        fn should_move_status_forward(
+            #[case] status: Status,
+            #[case] id: &'static str,
+            #[case] expected: Status,
+        ) {
+            let (_dir, path) = copy_todo_list();
+
+            let res = transition(
+                transition_parser(split_into_string_iter(id), status).unwrap(),
+                &mut JsonStore::new(&path),
+            );
+            assert!(res.is_ok(), "not Ok; res={:?}", res);
+
+            let todo_list = TodoList::load(&path).unwrap();
+            assert_eq!(todo_list.get(id.parse().unwrap()).unwrap().status, expected);
+        }
+
+        #[test]
+        fn should_add_next_occurrence_when_recurring_todo_done() {
+            let (_dir, path) = copy_todo_list();
+
+            update(
+                update_parser(split_into_string_iter("0 --repeat 2w")).unwrap(),
+                &mut JsonStore::new(&path),
+            )
+            .unwrap();
+            transition(
+                transition_parser(split_into_string_iter("0"), Status::Done).unwrap(),
+                &mut JsonStore::new(&path),
+            )
+            .unwrap();
+
+            let todo_list = TodoList::load(&path).unwrap();
+            let next = todo_list
+                .get(todo_list.get(0).unwrap().next.unwrap())
+                .unwrap();
+            assert_eq!(next.status, Status::ToDo);
+            assert_eq!(next.series, Some(0));
+            assert_eq!(next.recurrence, "every 2 weeks".parse().ok());
+        }
+
+        #[rstest]
+        #[case(Status::InProgress, "1")]
+        #[case(Status::InProgress, "2")]
+        #[case(Status::Done, "0 2")]
+        #[case(Status::Done, "9")]
+        fn should_not_move_status_backward_or_update_partially(
+            #[case] status: Status,
+            #[case] ids: &'static str,
+        ) {
+            let (_dir, path) = copy_todo_list();
+
+            let res = transition(
+                transition_parser(split_into_string_iter(ids), status).unwrap(),
+                &mut JsonStore::new(&path),
+            );
+            assert!(res.is_err(), "not err; res={:?}", res);
+
+            assert_eq!(
+                TodoList::load(&path).unwrap(),
+                TodoList::load("test_assets/todo-list.json").unwrap()
+            );
+        }
+    }
+}
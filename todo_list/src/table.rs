@@ -0,0 +1,739 @@
+use crate::date::{format_date, DATE_FORMAT};
+use crate::search::Hit;
+use crate::todo::Todo;
+use std::env;
+use std::str::FromStr;
+use terminal_size::{terminal_size, Width};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+const DEFAULT_WIDTH: usize = 80;
+const MIN_COLUMN_WIDTH: usize = 4;
+const ELLIPSIS: &str = "...";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Id,
+    Hash,
+    Title,
+    Description,
+    Status,
+    Priority,
+    Due,
+    Labels,
+    Parent,
+    BlockedBy,
+    Recurrence,
+    /// Relevance of a search hit; only [`render_hits`] fills it in.
+    Score,
+    /// Done and total direct subtasks of a parent; only [`render_tree`] fills it in.
+    Progress,
+}
+
+impl Column {
+    pub const ALL: [Column; 11] = [
+        Self::Id,
+        Self::Hash,
+        Self::Title,
+        Self::Description,
+        Self::Status,
+        Self::Priority,
+        Self::Due,
+        Self::Labels,
+        Self::Parent,
+        Self::BlockedBy,
+        Self::Recurrence,
+    ];
+
+    /// Columns shown when none are given.
+    pub const DEFAULT: [Column; 7] = [
+        Self::Id,
+        Self::Title,
+        Self::Description,
+        Self::Status,
+        Self::Priority,
+        Self::Due,
+        Self::Labels,
+    ];
+
+    /// Columns written by `export`: every field the CSV and TSV readers take back.
+    pub const EXPORT: [Column; 10] = [
+        Self::Id,
+        Self::Title,
+        Self::Description,
+        Self::Status,
+        Self::Priority,
+        Self::Due,
+        Self::Labels,
+        Self::Parent,
+        Self::BlockedBy,
+        Self::Recurrence,
+    ];
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::Hash => "hash",
+            Self::Title => "title",
+            Self::Description => "description",
+            Self::Status => "status",
+            Self::Priority => "priority",
+            Self::Due => "due",
+            Self::Labels => "labels",
+            Self::Parent => "parent",
+            Self::BlockedBy => "blocked_by",
+            Self::Recurrence => "recurrence",
+            Self::Score => "score",
+            Self::Progress => "done",
+        }
+    }
+
+    /// The cell for `todo`, with dates in ISO form so they parse back.
+    pub fn value(&self, todo: &Todo) -> String {
+        self.format(todo, DATE_FORMAT)
+    }
+
+    /// The cell for `todo`, with dates shown in `date_format` (a strftime pattern), or in
+    /// ISO form if that cannot format a date.
+    pub fn format(&self, todo: &Todo, date_format: &str) -> String {
+        match self {
+            Self::Id => todo.id.to_string(),
+            Self::Hash => todo.hash_id(),
+            Self::Title => todo.title.clone(),
+            Self::Description => todo.description.clone(),
+            Self::Status => todo.status.to_string(),
+            Self::Priority => todo.priority.to_string(),
+            Self::Due => todo
+                .due
+                .map(|x| {
+                    format_date(x, date_format).unwrap_or_else(|| x.format(DATE_FORMAT).to_string())
+                })
+                .unwrap_or_default(),
+            Self::Labels => todo
+                .labels
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", "),
+            Self::Parent => todo.parent.map(|x| x.to_string()).unwrap_or_default(),
+            Self::BlockedBy => todo
+                .blocked_by
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            Self::Recurrence => todo.recurrence.map(|x| x.to_string()).unwrap_or_default(),
+            Self::Score | Self::Progress => String::new(),
+        }
+    }
+
+    /// Share of the remaining terminal width given to free-text columns;
+    /// `None` for columns that always get their natural width.
+    fn weight(&self) -> Option<usize> {
+        match self {
+            Self::Id
+            | Self::Hash
+            | Self::Status
+            | Self::Priority
+            | Self::Due
+            | Self::Parent
+            | Self::BlockedBy
+            | Self::Score
+            | Self::Progress => None,
+            Self::Title => Some(3),
+            Self::Description => Some(4),
+            Self::Labels | Self::Recurrence => Some(2),
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = if s == "label" { "labels" } else { s };
+        Self::ALL
+            .into_iter()
+            .find(|x| x.header() == s)
+            .ok_or_else(|| format!("invalid column; {}", s))
+    }
+}
+
+/// Parses a comma-separated column list such as `id,title,status`.
+pub fn parse_columns(s: &str) -> Result<Vec<Column>, String> {
+    s.split(',').map(|x| x.trim().parse()).collect()
+}
+
+/// Width of the table: `COLUMNS` if set, otherwise the width of the terminal on stdout.
+pub fn terminal_width() -> usize {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .filter(|&x| x > 0)
+        .or_else(|| terminal_size().map(|(Width(w), _)| w as usize))
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+fn elide(s: &str, width: usize) -> String {
+    if s.width() <= width {
+        return s.to_string();
+    }
+
+    debug_assert!(width > ELLIPSIS.len());
+
+    let mut elided = String::new();
+    let mut elided_width = 0;
+    for c in s.chars() {
+        let w = c.width().unwrap_or(0);
+        if elided_width + w > width - ELLIPSIS.len() {
+            break;
+        }
+        elided.push(c);
+        elided_width += w;
+    }
+
+    elided + ELLIPSIS
+}
+
+/// Splits `s` into lines no wider than `width`, breaking at whitespace where possible.
+pub fn wrap(s: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in s.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let sep = if line.is_empty() { 0 } else { 1 };
+            if line.width() + sep + word.width() <= width {
+                if sep == 1 {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for c in word.chars() {
+                if line.width() + c.width().unwrap_or(0) > width {
+                    lines.push(std::mem::take(&mut line));
+                }
+                line.push(c);
+            }
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Like [`wrap`], but repeats the leading spaces of `s` on every line, which keeps
+/// subtasks indented in a tree view.
+fn wrap_indented(s: &str, width: usize) -> Vec<String> {
+    let body = s.trim_start_matches(' ');
+    let indent = &s[..s.len() - body.len()];
+    if indent.is_empty() || indent.len() >= width {
+        return wrap(s, width);
+    }
+
+    wrap(body, width - indent.len())
+        .into_iter()
+        .map(|x| format!("{}{}", indent, x))
+        .collect()
+}
+
+fn pad(s: &str, width: usize, right_align: bool) -> String {
+    let padding = " ".repeat(width.saturating_sub(s.width()));
+    if right_align {
+        padding + s
+    } else {
+        s.to_string() + &padding
+    }
+}
+
+/// Gives fixed columns their natural width and splits what is left of `total_width`
+/// among the weighted columns, never widening a column beyond its natural width.
+fn allocate_widths(columns: &[Column], natural: &[usize], total_width: usize) -> Vec<usize> {
+    let mut widths = natural.to_vec();
+    let fixed = columns
+        .iter()
+        .zip(natural)
+        .filter(|(column, _)| column.weight().is_none())
+        .map(|(_, &w)| w)
+        .sum::<usize>();
+    let mut budget = total_width.saturating_sub(fixed + columns.len().saturating_sub(1));
+
+    let mut pending = (0..columns.len())
+        .filter(|&i| columns[i].weight().is_some())
+        .collect::<Vec<_>>();
+    loop {
+        let weights = pending
+            .iter()
+            .map(|&i| columns[i].weight().unwrap())
+            .sum::<usize>();
+        let (fits, rest): (Vec<_>, Vec<_>) = pending
+            .iter()
+            .partition(|&&i| natural[i] * weights <= budget * columns[i].weight().unwrap());
+        if fits.is_empty() {
+            break;
+        }
+        budget -= fits.iter().map(|&i| natural[i]).sum::<usize>();
+        pending = rest;
+    }
+
+    let weights = pending
+        .iter()
+        .map(|&i| columns[i].weight().unwrap())
+        .sum::<usize>();
+    let mut remainder = budget;
+    for &i in &pending {
+        widths[i] = budget * columns[i].weight().unwrap() / weights;
+        remainder -= widths[i];
+    }
+    for &i in &pending {
+        if remainder > 0 {
+            widths[i] += 1;
+            remainder -= 1;
+        }
+        widths[i] = widths[i].max(MIN_COLUMN_WIDTH);
+    }
+
+    widths
+}
+
+pub fn render<'a>(
+    todos: impl Iterator<Item = &'a Todo>,
+    columns: &[Column],
+    total_width: usize,
+    wrap_text: bool,
+    date_format: &str,
+) -> String {
+    let rows = todos
+        .map(|todo| {
+            columns
+                .iter()
+                .map(|x| x.format(todo, date_format))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    render_rows(&rows, columns, total_width, wrap_text)
+}
+
+/// A todo in a tree view, indented by `depth`; a parent carries the `(done, total)`
+/// counts of its direct subtasks.
+pub struct Node<'a> {
+    pub todo: &'a Todo,
+    pub depth: usize,
+    pub progress: Option<(usize, usize)>,
+}
+
+/// Like [`render`], indenting each title by its depth and showing a parent's progress,
+/// e.g. `3/5`, in [`Column::Progress`].
+pub fn render_tree(
+    nodes: &[Node],
+    columns: &[Column],
+    total_width: usize,
+    wrap_text: bool,
+    date_format: &str,
+) -> String {
+    let rows = nodes
+        .iter()
+        .map(|node| {
+            columns
+                .iter()
+                .map(|x| match x {
+                    Column::Title => format!("{}{}", "  ".repeat(node.depth), node.todo.title),
+                    Column::Progress => node
+                        .progress
+                        .map(|(done, total)| format!("{}/{}", done, total))
+                        .unwrap_or_default(),
+                    x => x.format(node.todo, date_format),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    render_rows(&rows, columns, total_width, wrap_text)
+}
+
+/// Like [`render`], showing each hit's relevance in [`Column::Score`].
+pub fn render_hits(
+    hits: &[Hit],
+    columns: &[Column],
+    total_width: usize,
+    wrap_text: bool,
+    date_format: &str,
+) -> String {
+    let rows = hits
+        .iter()
+        .map(|hit| {
+            columns
+                .iter()
+                .map(|x| match x {
+                    Column::Score => format!("{:.2}", hit.score),
+                    x => x.format(hit.todo, date_format),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    render_rows(&rows, columns, total_width, wrap_text)
+}
+
+fn render_rows(
+    rows: &[Vec<String>],
+    columns: &[Column],
+    total_width: usize,
+    wrap_text: bool,
+) -> String {
+    let natural = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            rows.iter()
+                .flat_map(|row| row[i].split('\n'))
+                .map(|x| x.width())
+                .chain([column.header().width()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+    let widths = allocate_widths(columns, &natural, total_width);
+
+    let fmt_row = |values: &[String]| -> String {
+        let cells = values
+            .iter()
+            .zip(&widths)
+            .map(|(value, &width)| {
+                if wrap_text {
+                    wrap_indented(value, width)
+                } else {
+                    vec![elide(&value.replace('\n', " "), width)]
+                }
+            })
+            .collect::<Vec<_>>();
+        let height = cells.iter().map(|x| x.len()).max().unwrap_or(1);
+
+        (0..height)
+            .map(|line| {
+                cells
+                    .iter()
+                    .zip(columns.iter().zip(&widths))
+                    .map(|(cell, (column, &width))| {
+                        let s = cell.get(line).map(String::as_str).unwrap_or("");
+                        let right_align = matches!(
+                            column,
+                            Column::Id | Column::Parent | Column::Score | Column::Progress
+                        );
+                        pad(s, width, right_align)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let headers = fmt_row(
+        &columns
+            .iter()
+            .map(|x| x.header().to_string())
+            .collect::<Vec<_>>(),
+    );
+    let underline = "-".repeat(widths.iter().sum::<usize>() + widths.len().saturating_sub(1));
+
+    [headers, underline]
+        .into_iter()
+        .chain(rows.iter().map(|x| fmt_row(x)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn display_tree(nodes: &[Node], columns: &[Column], wrap_text: bool, date_format: &str) {
+    println!(
+        "{}",
+        render_tree(nodes, columns, terminal_width(), wrap_text, date_format)
+    );
+}
+
+pub fn display_hits(hits: &[Hit], columns: &[Column], wrap_text: bool, date_format: &str) {
+    println!(
+        "{}",
+        render_hits(hits, columns, terminal_width(), wrap_text, date_format)
+    );
+}
+
+pub fn display<'a>(
+    todos: impl Iterator<Item = &'a Todo>,
+    columns: &[Column],
+    wrap_text: bool,
+    date_format: &str,
+) {
+    println!(
+        "{}",
+        render(todos, columns, terminal_width(), wrap_text, date_format)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::labels;
+    use crate::todo::{Status, TodoList};
+    use rstest::*;
+
+    fn create_todo_list() -> TodoList {
+        TodoList::new(
+            vec![
+                Todo {
+                    id: 0,
+                    title: String::from("title0"),
+                    description: String::from("description0"),
+                    status: Status::ToDo,
+                    labels: labels(&[]),
+                    ..Default::default()
+                },
+                Todo {
+                    id: 1,
+                    title: String::from("title1").repeat(10),
+                    description: String::from("description1").repeat(10),
+                    status: Status::InProgress,
+                    labels: labels(&[&"label1".repeat(10), "label3"]),
+                    ..Default::default()
+                },
+                Todo {
+                    id: 12,
+                    title: String::from("タイトル2"),
+                    description: String::from("説明2 🎉"),
+                    status: Status::Done,
+                    labels: labels(&["label2"]),
+                    ..Default::default()
+                },
+            ]
+            .into_iter(),
+        )
+    }
+
+    #[test]
+    fn should_display_todo_list_properly() {
+        let todo_list = create_todo_list();
+        display(
+            todo_list.list().iter(),
+            &Column::DEFAULT,
+            false,
+            DATE_FORMAT,
+        );
+    }
+
+    #[rstest]
+    #[case(60)]
+    #[case(80)]
+    #[case(120)]
+    fn should_fit_rows_to_total_width(#[case] total_width: usize) {
+        let todo_list = create_todo_list();
+
+        let table = render(
+            todo_list.list().iter(),
+            &Column::DEFAULT,
+            total_width,
+            false,
+            DATE_FORMAT,
+        );
+
+        for line in table.lines() {
+            assert!(line.width() <= total_width, "too wide; line={line}");
+        }
+        assert_eq!(table.lines().count(), 5);
+    }
+
+    #[test]
+    fn should_align_wide_characters_by_display_width() {
+        let todo_list = create_todo_list();
+        let columns = [Column::Title, Column::Status];
+
+        let table = render(todo_list.list().iter(), &columns, 200, false, DATE_FORMAT);
+        let status_offsets = table
+            .lines()
+            .filter(|x| !x.starts_with('-'))
+            .map(|x| {
+                let (title, _) = x.rsplit_once(' ').unwrap();
+                title.width()
+            })
+            .collect::<Vec<_>>();
+
+        assert!(
+            status_offsets.windows(2).all(|x| x[0] == x[1]),
+            "misaligned; table=\n{table}"
+        );
+    }
+
+    #[test]
+    fn should_wrap_long_values_instead_of_eliding() {
+        let todo_list = create_todo_list();
+        let columns = [Column::Id, Column::Description];
+
+        let table = render(todo_list.list().iter(), &columns, 40, true, DATE_FORMAT);
+
+        assert!(!table.contains(ELLIPSIS), "elided; table=\n{table}");
+        assert!(table.lines().all(|x| x.width() <= 40));
+        assert_eq!(
+            table
+                .replace(['\n', ' '], "")
+                .matches("description1")
+                .count(),
+            10
+        );
+    }
+
+    #[rstest]
+    #[case("title", 10, "title")]
+    #[case("title1title1", 10, "title1t...")]
+    #[case("タイトルタイトル", 10, "タイト...")]
+    #[case("🎉🎉🎉🎉🎉🎉", 9, "🎉🎉🎉...")]
+    fn should_elide_by_display_width(
+        #[case] s: &str,
+        #[case] width: usize,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(elide(s, width), expected);
+        assert!(elide(s, width).width() <= width);
+    }
+
+    #[rstest]
+    #[case("a bb ccc", 4, vec!["a bb", "ccc"])]
+    #[case("abcdefgh", 3, vec!["abc", "def", "gh"])]
+    #[case("タイトル", 5, vec!["タイ", "トル"])]
+    #[case("a\nb", 4, vec!["a", "b"])]
+    #[case("", 4, vec![""])]
+    fn should_wrap_by_display_width(
+        #[case] s: &str,
+        #[case] width: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        assert_eq!(wrap(s, width), expected);
+    }
+
+    #[rstest]
+    #[case(&[Column::Id, Column::Title], &[2, 10], 80, vec![2, 10])]
+    #[case(&[Column::Id, Column::Title, Column::Labels], &[2, 100, 100], 33, vec![2, 18, 11])]
+    #[case(&[Column::Title, Column::Description], &[5, 100], 30, vec![5, 24])]
+    #[case(&[Column::Id, Column::Title], &[2, 100], 4, vec![2, MIN_COLUMN_WIDTH])]
+    fn should_allocate_widths(
+        #[case] columns: &[Column],
+        #[case] natural: &[usize],
+        #[case] total_width: usize,
+        #[case] expected: Vec<usize>,
+    ) {
+        assert_eq!(allocate_widths(columns, natural, total_width), expected);
+    }
+
+    #[rstest]
+    #[case("id", Ok(Column::Id))]
+    #[case("description", Ok(Column::Description))]
+    #[case("labels", Ok(Column::Labels))]
+    #[case("label", Ok(Column::Labels))]
+    #[case("due", Ok(Column::Due))]
+    #[case("hash", Ok(Column::Hash))]
+    #[case("size", Err(String::from("invalid column; size")))]
+    #[case("score", Err(String::from("invalid column; score")))]
+    fn should_parse_column(#[case] s: &str, #[case] expected: Result<Column, String>) {
+        assert_eq!(s.parse::<Column>(), expected);
+    }
+
+    #[test]
+    fn should_indent_subtasks_and_show_progress() {
+        let todo_list = create_todo_list();
+        let todos = todo_list.list();
+        let nodes = [
+            Node {
+                todo: &todos[0],
+                depth: 0,
+                progress: Some((1, 2)),
+            },
+            Node {
+                todo: &todos[2],
+                depth: 1,
+                progress: None,
+            },
+        ];
+
+        let columns = [Column::Id, Column::Title, Column::Progress];
+        let table = render_tree(&nodes, &columns, 80, false, DATE_FORMAT);
+
+        assert_eq!(
+            table.lines().skip(2).collect::<Vec<_>>(),
+            vec![" 0 title0       1/2", "12   タイトル2"]
+        );
+
+        let table = render_tree(
+            &nodes,
+            &[Column::Title, Column::Progress],
+            13,
+            true,
+            DATE_FORMAT,
+        );
+        assert_eq!(
+            table.lines().skip(2).collect::<Vec<_>>(),
+            vec!["title0    1/2", "  タイト", "  ル2"]
+        );
+    }
+
+    #[test]
+    fn should_keep_progress_of_a_parent_whose_title_is_elided() {
+        let todo = Todo {
+            title: String::from("release the new version of the product"),
+            ..Default::default()
+        };
+        let nodes = [Node {
+            todo: &todo,
+            depth: 0,
+            progress: Some((1, 2)),
+        }];
+
+        let table = render_tree(
+            &nodes,
+            &[Column::Title, Column::Progress],
+            20,
+            false,
+            DATE_FORMAT,
+        );
+
+        assert_eq!(table.lines().nth(2), Some("release the ...  1/2"));
+    }
+
+    #[test]
+    fn should_show_score_of_search_hits() {
+        let todo_list = create_todo_list();
+        let todos = todo_list.list();
+        let hits = [
+            Hit {
+                todo: &todos[2],
+                score: 12.5,
+            },
+            Hit {
+                todo: &todos[0],
+                score: 0.333,
+            },
+        ];
+
+        let table = render_hits(&hits, &[Column::Score, Column::Id], 80, false, DATE_FORMAT);
+
+        assert_eq!(
+            table.lines().collect::<Vec<_>>(),
+            vec!["score id", "--------", "12.50 12", " 0.33  0"]
+        );
+    }
+
+    #[test]
+    fn should_show_due_dates_in_given_format() {
+        let todo = Todo {
+            due: chrono::NaiveDate::from_ymd_opt(2026, 10, 31),
+            ..Default::default()
+        };
+
+        assert_eq!(Column::Due.value(&todo), "2026-10-31");
+        assert_eq!(Column::Due.format(&todo, "%d/%m/%Y"), "31/10/2026");
+        assert_eq!(Column::Due.format(&todo, "%H:%M"), "2026-10-31");
+    }
+}
@@ -1,3 +1,21 @@
+use crate::todo::Labels;
+use std::fs;
+use tempfile::{tempdir, TempDir};
+
 pub fn split_into_string_iter(s: &'static str) -> impl Iterator<Item = String> {
     s.split(' ').map(String::from)
 }
+
+pub fn labels(xs: &[&str]) -> Labels {
+    xs.iter().map(|x| x.to_string()).collect()
+}
+
+/// Copies `test_assets/todo-list.json` into a new temporary directory, which is removed
+/// when the returned [`TempDir`] is dropped.
+pub fn copy_todo_list() -> (TempDir, String) {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("todo-list.json");
+    fs::copy("test_assets/todo-list.json", &path).unwrap();
+    let path = path.to_str().unwrap().to_string();
+    (dir, path)
+}
@@ -1,11 +1,20 @@
-use serde::{Deserialize, Serialize};
-use std::error::Error;
+use crate::date::local_date;
+use crate::migrate::migrate;
+use crate::recurrence::Recurrence;
+use crate::Error;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt;
-use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::path::Path;
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Deserialize, Serialize)]
 pub enum Status {
+    #[default]
     ToDo,
     InProgress,
     Done,
@@ -25,70 +34,1074 @@ impl fmt::Display for Status {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.to_lowercase()[..] {
+            "todo" => Ok(Self::ToDo),
+            "inprogress" => Ok(Self::InProgress),
+            "done" => Ok(Self::Done),
+            _ => Err(format!("invalid status; {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Deserialize, Serialize)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Low => "Low",
+                Self::Normal => "Normal",
+                Self::High => "High",
+                Self::Urgent => "Urgent",
+            }
+        )
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.to_lowercase()[..] {
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::High),
+            "urgent" => Ok(Self::Urgent),
+            _ => Err(format!("invalid priority; {}", s)),
+        }
+    }
+}
+
+pub type Labels = BTreeSet<String>;
+
+/// Accepts both the current `labels` array and the single `label` string of older files,
+/// where an empty string meant no label.
+fn deserialize_labels<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Labels, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Labels),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(x) if x.is_empty() => Labels::new(),
+        OneOrMany::One(x) => Labels::from([x]),
+        OneOrMany::Many(x) => x,
+    })
+}
+
+/// Splits comma-separated labels as given on the command line, dropping empty ones.
+pub fn split_labels(s: &str) -> impl Iterator<Item = String> + '_ {
+    s.split(',')
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .map(String::from)
+}
+
+#[derive(Debug, PartialEq, Eq, Default)]
 pub struct PartialTodo {
     pub title: String,
     pub description: String,
-    pub label: String,
+    pub labels: Labels,
+    pub due: Option<NaiveDate>,
+    pub priority: Priority,
+    pub parent: Option<usize>,
+    pub recurrence: Option<Recurrence>,
+}
+
+/// Fields to change in [`TodoList::update`]; `None` leaves a field as is,
+/// and `due: Some(None)` clears the due date (`parent` and `recurrence` likewise).
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct TodoPatch {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub labels: Option<Labels>,
+    pub status: Option<Status>,
+    pub due: Option<Option<NaiveDate>>,
+    pub priority: Option<Priority>,
+    pub parent: Option<Option<usize>>,
+    pub recurrence: Option<Option<Recurrence>>,
 }
 
-#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+/// One entry of a todo's status log, appended whenever [`TodoList::update`] changes the status.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct StatusChange {
+    pub from: Status,
+    pub to: Status,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize)]
 pub struct Todo {
     pub id: usize,
     pub title: String,
     pub description: String,
     pub status: Status,
-    pub label: String,
+    #[serde(alias = "label", deserialize_with = "deserialize_labels", default)]
+    pub labels: Labels,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<StatusChange>,
+    /// The todo this is a subtask of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<usize>,
+    /// Todos that must be done before this one can start.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub blocked_by: BTreeSet<usize>,
+    /// How the todo repeats once done.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    /// The first todo of the series this is a later occurrence of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series: Option<usize>,
+    /// The occurrence added when this todo was done; completing it again adds no other.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<usize>,
+}
+
+const HASH_ID_LEN: usize = 7;
+
+impl Todo {
+    /// Short hex id derived from the id and creation time, for referring to a todo outside
+    /// this list. It never changes once the todo exists and is the same on every machine.
+    pub fn hash_id(&self) -> String {
+        // 64-bit FNV-1a; std's hashers are not guaranteed stable across releases.
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let created_at = self
+            .created_at
+            .map(|x| x.timestamp_nanos_opt().unwrap_or_default());
+        for byte in (self.id as u64)
+            .to_le_bytes()
+            .into_iter()
+            .chain(created_at.unwrap_or_default().to_le_bytes())
+        {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+
+        format!("{:016x}", hash)[..HASH_ID_LEN].to_string()
+    }
+
+    /// Time from first entering `InProgress` to completion, if the todo is done and was started.
+    pub fn cycle_time(&self) -> Option<TimeDelta> {
+        let started_at = self.history.iter().find(|x| x.to == Status::InProgress)?.at;
+        Some(self.completed_at? - started_at)
+    }
+
+    fn set_status(&mut self, status: Status, now: DateTime<Utc>) {
+        if status == self.status {
+            return;
+        }
+
+        self.history.push(StatusChange {
+            from: self.status,
+            to: status,
+            at: now,
+        });
+        self.status = status;
+        self.completed_at = (status == Status::Done).then_some(now);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct Removal {
+    pub removed: Vec<usize>,
+    pub not_found: Vec<usize>,
+    /// Children moved up to the removed todo's parent by [`ChildPolicy::Reparent`].
+    pub reparented: Vec<usize>,
+    /// Todos that were blocked by a removed todo and no longer are.
+    pub unlinked: Vec<usize>,
+}
+
+/// What [`TodoList::remove_with`] does with the children of a removed todo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChildPolicy {
+    /// Fails unless the children are removed too.
+    #[default]
+    Refuse,
+    /// Removes every descendant as well.
+    Cascade,
+    /// Moves the children up to the removed todo's parent, or to the top level.
+    Reparent,
+}
+
+impl FromStr for ChildPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "refuse" => Ok(Self::Refuse),
+            "cascade" => Ok(Self::Cascade),
+            "reparent" => Ok(Self::Reparent),
+            _ => Err(format!("invalid child policy; {}", s)),
+        }
+    }
+}
+
+/// What [`TodoList::import`] does with an imported todo whose id is already taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conflict {
+    Renumber,
+    Skip,
+    Overwrite,
+}
+
+impl FromStr for Conflict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "renumber" => Ok(Self::Renumber),
+            "skip" => Ok(Self::Skip),
+            "overwrite" => Ok(Self::Overwrite),
+            _ => Err(format!("invalid conflict policy; {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct ImportReport {
+    pub added: Vec<usize>,
+    pub renumbered: Vec<(usize, usize)>,
+    pub skipped: Vec<usize>,
+    pub overwritten: Vec<usize>,
+    /// Imported todos that lost a link to a todo missing from both the import and the list,
+    /// or one that would have made a cycle.
+    pub detached: Vec<usize>,
+}
+
+/// Version written by [`TodoList::save`]; older files are upgraded on load by
+/// [`crate::migrate::migrate`].
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Layout of the file written by [`TodoList::save`].
+#[derive(Serialize)]
+struct StoredList<'a> {
+    version: u32,
+    next_id: usize,
+    todos: &'a [Todo],
 }
 
-#[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Deserialize)]
+struct StoredFile {
+    next_id: usize,
+    todos: Vec<Todo>,
+}
+
+/// Todos plus the counter new ids are drawn from. The counter only grows, so an id is never
+/// handed out again after its todo is removed.
+#[derive(Debug, PartialEq, Eq)]
 pub struct TodoList {
+    next_id: usize,
     list: Vec<Todo>,
 }
 
 impl TodoList {
     pub fn new(iter: impl Iterator<Item = Todo>) -> Self {
+        let list = Vec::from_iter(iter);
         TodoList {
-            list: Vec::from_iter(iter),
+            next_id: Self::id_after(&list),
+            list,
         }
     }
 
-    pub fn load(file_path: &str) -> Result<Self, Box<dyn Error>> {
-        let file = File::open(file_path)?;
+    fn id_after(todos: &[Todo]) -> usize {
+        todos
+            .iter()
+            .map(|x| x.id.saturating_add(1))
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn load(file_path: &str) -> Result<Self, Error> {
+        let file = File::open(file_path).map_err(Error::io(file_path))?;
         let buf = BufReader::new(file);
-        let todos: Vec<Todo> = serde_json::from_reader(buf)?;
+        let mut value: Value = serde_json::from_reader(buf)?;
+        migrate(&mut value)?;
+        let StoredFile { next_id, todos } = serde_json::from_value(value)?;
+
+        Ok(Self::with_next_id(todos.into_iter(), next_id))
+    }
+
+    /// Builds a list whose counter resumes at `next_id`, or after the highest id if that is
+    /// larger.
+    pub fn with_next_id(iter: impl Iterator<Item = Todo>, next_id: usize) -> Self {
+        let list = Vec::from_iter(iter);
+        TodoList {
+            next_id: next_id.max(Self::id_after(&list)),
+            list,
+        }
+    }
+
+    pub fn load_or_new(file_path: &str) -> Result<Self, Error> {
+        match File::open(file_path) {
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::new(Vec::new().into_iter())),
+            _ => Self::load(file_path),
+        }
+    }
+
+    /// The id the next added todo will get.
+    pub fn next_id(&self) -> usize {
+        self.next_id
+    }
 
-        Ok(Self::new(todos.into_iter()))
+    fn allocate_id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
     }
 
-    fn get_next_id(&self) -> usize {
-        let ids = self.list.iter().map(|x| x.id).collect::<Vec<_>>();
-        (0..(self.list.len() + 1))
-            .filter(|x| !ids.contains(x))
-            .min()
-            .unwrap()
+    pub fn add(&mut self, todo: &PartialTodo) -> usize {
+        self.add_at(todo, Utc::now())
     }
 
-    pub fn add(&mut self, todo: &PartialTodo) {
+    /// Like [`TodoList::add`], stamping the new todo as created at `now`.
+    pub fn add_at(&mut self, todo: &PartialTodo, now: DateTime<Utc>) -> usize {
+        let id = self.allocate_id();
         self.list.push(Todo {
-            id: self.get_next_id(),
+            id,
             title: todo.title.clone(),
             description: todo.description.clone(),
             status: Status::ToDo,
-            label: todo.label.clone(),
+            labels: todo.labels.clone(),
+            due: todo.due,
+            priority: todo.priority,
+            parent: todo.parent,
+            recurrence: todo.recurrence,
+            created_at: Some(now),
+            updated_at: Some(now),
+            ..Default::default()
+        });
+
+        id
+    }
+
+    /// Inserts todos keeping their ids, resolving collisions with `on_conflict`. Links
+    /// between the imported todos follow them to the ids they end up with, with a skipped
+    /// todo standing in for the one already in the list.
+    pub fn import(&mut self, todos: Vec<Todo>, on_conflict: Conflict) -> ImportReport {
+        let mut report = ImportReport::default();
+        let mut positions = self.positions();
+        let mut ids = HashMap::new();
+        let mut stored = Vec::new();
+
+        for mut todo in todos {
+            let from = todo.id;
+            match positions.get(&todo.id) {
+                None => {
+                    report.added.push(todo.id);
+                    self.next_id = self.next_id.max(todo.id.saturating_add(1));
+                }
+                Some(_) if on_conflict == Conflict::Skip => {
+                    report.skipped.push(todo.id);
+                    ids.entry(from).or_insert(from);
+                    continue;
+                }
+                Some(&index) if on_conflict == Conflict::Overwrite => {
+                    report.overwritten.push(todo.id);
+                    ids.entry(from).or_insert(from);
+                    stored.push(index);
+                    self.list[index] = todo;
+                    continue;
+                }
+                Some(_) => {
+                    todo.id = self.allocate_id();
+                    report.renumbered.push((from, todo.id));
+                }
+            }
+            ids.entry(from).or_insert(todo.id);
+            positions.insert(todo.id, self.list.len());
+            stored.push(self.list.len());
+            self.list.push(todo);
+        }
+
+        report.detached = self.relink(&stored, &ids, &positions);
+        report
+    }
+
+    /// Where each todo is in the list, by id.
+    fn positions(&self) -> HashMap<usize, usize> {
+        self.list
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (x.id, i))
+            .collect()
+    }
+
+    /// Points the links of the todos at `stored` through `ids`, the old and new ids of an
+    /// import, and returns the ids of those that had to drop a link. Links to todos not in
+    /// `ids` stay if the todo is in the list. Links are restored one at a time, each only if
+    /// it makes no cycle with those before it.
+    fn relink(
+        &mut self,
+        stored: &[usize],
+        ids: &HashMap<usize, usize>,
+        positions: &HashMap<usize, usize>,
+    ) -> Vec<usize> {
+        let follow = |id: usize| match ids.get(&id) {
+            Some(&x) => Some(x),
+            None => positions.contains_key(&id).then_some(id),
+        };
+
+        let mut detached = BTreeSet::new();
+        let mut parents = Vec::new();
+        let mut blockers = Vec::new();
+        for &index in stored {
+            let todo = &mut self.list[index];
+            if let Some(parent) = todo.parent.take() {
+                match follow(parent) {
+                    Some(x) => parents.push((index, x)),
+                    None => {
+                        detached.insert(todo.id);
+                    }
+                }
+            }
+            for blocker in std::mem::take(&mut todo.blocked_by) {
+                match follow(blocker) {
+                    Some(x) => blockers.push((index, x)),
+                    None => {
+                        detached.insert(todo.id);
+                    }
+                }
+            }
+            for link in [&mut todo.series, &mut todo.next] {
+                if let Some(x) = link.take() {
+                    *link = follow(x);
+                    if link.is_none() {
+                        detached.insert(todo.id);
+                    }
+                }
+            }
+        }
+
+        for (index, parent) in parents {
+            let id = self.list[index].id;
+            let mut ancestor = Some(parent);
+            let mut seen = BTreeSet::new();
+            while let Some(x) = ancestor.filter(|&x| x != id && seen.insert(x)) {
+                ancestor = positions.get(&x).and_then(|&i| self.list[i].parent);
+            }
+            if ancestor == Some(id) {
+                detached.insert(id);
+            } else {
+                self.list[index].parent = Some(parent);
+            }
+        }
+
+        for (index, blocker) in blockers {
+            let id = self.list[index].id;
+            let get = |x: usize| positions.get(&x).map(|&i| &self.list[i]);
+            if Self::blocking_path(get, blocker, id).is_some() {
+                detached.insert(id);
+            } else {
+                self.list[index].blocked_by.insert(blocker);
+            }
+        }
+
+        detached.into_iter().collect()
+    }
+
+    /// Inserts todos under newly assigned ids, ignoring the ids they carry.
+    /// Todos without a creation time are stamped as created now.
+    pub fn append(&mut self, todos: Vec<Todo>) -> Vec<usize> {
+        let now = Utc::now();
+        todos
+            .into_iter()
+            .map(|mut todo| {
+                todo.id = self.allocate_id();
+                todo.created_at.get_or_insert(now);
+                todo.updated_at.get_or_insert(now);
+                self.list.push(todo);
+                self.list.last().unwrap().id
+            })
+            .collect()
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Todo> {
+        self.list.iter().find(|x| x.id == id)
+    }
+
+    /// Resolves a numeric id or a prefix of a [`Todo::hash_id`] to the todo's id.
+    pub fn resolve(&self, s: &str) -> Result<usize, Error> {
+        if let Some(todo) = s.parse().ok().and_then(|id| self.get(id)) {
+            return Ok(todo.id);
+        }
+
+        let prefix = s.to_lowercase();
+        let mut matches = self
+            .list
+            .iter()
+            .filter(|x| x.hash_id().starts_with(&prefix));
+        match (matches.next(), matches.next()) {
+            (Some(todo), None) if !prefix.is_empty() => Ok(todo.id),
+            (Some(_), Some(_)) if !prefix.is_empty() => {
+                Err(Error::Conflict(format!("ambiguous id; {}", s)))
+            }
+            _ => Err(Error::not_found(s)),
+        }
+    }
+
+    pub fn update(&mut self, id: usize, patch: &TodoPatch) -> Result<&Todo, Error> {
+        self.update_at(id, patch, Utc::now())
+    }
+
+    /// Like [`TodoList::update`], recording `now` as the update time and, if the status
+    /// changes, as the time of the transition.
+    pub fn update_at(
+        &mut self,
+        id: usize,
+        patch: &TodoPatch,
+        now: DateTime<Utc>,
+    ) -> Result<&Todo, Error> {
+        if patch.title.as_ref().is_some_and(|x| x.is_empty()) {
+            return Err(Error::Invalid(String::from("title must not be empty")));
+        }
+        if let Some(Some(parent)) = patch.parent {
+            self.check_parent(Some(id), parent)?;
+        }
+
+        let todo = match self.list.iter_mut().find(|x| x.id == id) {
+            Some(x) => x,
+            None => return Err(Error::not_found(id)),
+        };
+
+        if let Some(title) = &patch.title {
+            todo.title = title.clone();
+        }
+        if let Some(description) = &patch.description {
+            todo.description = description.clone();
+        }
+        if let Some(labels) = &patch.labels {
+            todo.labels = labels.clone();
+        }
+        if let Some(status) = patch.status {
+            todo.set_status(status, now);
+        }
+        if let Some(due) = patch.due {
+            todo.due = due;
+        }
+        if let Some(priority) = patch.priority {
+            todo.priority = priority;
+        }
+        if let Some(parent) = patch.parent {
+            todo.parent = parent;
+        }
+        if let Some(recurrence) = patch.recurrence {
+            todo.recurrence = recurrence;
+        }
+        todo.updated_at = Some(now);
+
+        if todo.status == Status::Done && todo.recurrence.is_some() && todo.next.is_none() {
+            self.recur(id, now);
+        }
+
+        Ok(self.get(id).unwrap())
+    }
+
+    /// Adds the occurrence following the done todo `id`, due as its recurrence says,
+    /// and links the two. Returns the new todo's id.
+    fn recur(&mut self, id: usize, now: DateTime<Utc>) -> Option<usize> {
+        let todo = self.get(id)?;
+        let recurrence = todo.recurrence?;
+        let due = recurrence.next(todo.due, local_date(now))?;
+        let series = todo.series.unwrap_or(id);
+        let partial = PartialTodo {
+            title: todo.title.clone(),
+            description: todo.description.clone(),
+            labels: todo.labels.clone(),
+            due: Some(due),
+            priority: todo.priority,
+            parent: todo.parent,
+            recurrence: Some(recurrence),
+        };
+
+        let next = self.add_at(&partial, now);
+        for todo in self.list.iter_mut() {
+            if todo.id == next {
+                todo.series = Some(series);
+            } else if todo.id == id {
+                todo.next = Some(next);
+            }
+        }
+
+        Some(next)
+    }
+
+    /// Adds `label` to the given todos and returns the ids of those that did not have it yet.
+    pub fn add_label(&mut self, label: &str, ids: &[usize]) -> Result<Vec<usize>, Error> {
+        if label.is_empty() {
+            return Err(Error::Invalid(String::from("label must not be empty")));
+        }
+        if let Some(&id) = ids.iter().find(|&&id| self.get(id).is_none()) {
+            return Err(Error::not_found(id));
+        }
+
+        let now = Utc::now();
+        Ok(self
+            .list
+            .iter_mut()
+            .filter(|x| ids.contains(&x.id))
+            .filter_map(|x| {
+                x.labels.insert(label.to_string()).then(|| {
+                    x.updated_at = Some(now);
+                    x.id
+                })
+            })
+            .collect())
+    }
+
+    /// Removes `label` from the given todos, or from every todo if `ids` is empty,
+    /// and returns the ids of the todos that had it.
+    pub fn remove_label(&mut self, label: &str, ids: &[usize]) -> Result<Vec<usize>, Error> {
+        if let Some(&id) = ids.iter().find(|&&id| self.get(id).is_none()) {
+            return Err(Error::not_found(id));
+        }
+
+        let now = Utc::now();
+        Ok(self
+            .list
+            .iter_mut()
+            .filter(|x| ids.is_empty() || ids.contains(&x.id))
+            .filter_map(|x| {
+                x.labels.remove(label).then(|| {
+                    x.updated_at = Some(now);
+                    x.id
+                })
+            })
+            .collect())
+    }
+
+    /// Renames `from` to `to` on every todo, merging into `to` where both are present,
+    /// and returns the ids of the todos affected.
+    pub fn rename_label(&mut self, from: &str, to: &str) -> Result<Vec<usize>, Error> {
+        if to.is_empty() {
+            return Err(Error::Invalid(String::from("label must not be empty")));
+        }
+
+        let now = Utc::now();
+        Ok(self
+            .list
+            .iter_mut()
+            .filter_map(|x| {
+                x.labels.remove(from).then(|| {
+                    x.labels.insert(to.to_string());
+                    x.updated_at = Some(now);
+                    x.id
+                })
+            })
+            .collect())
+    }
+
+    /// Every label in use with the number of todos carrying it.
+    pub fn labels(&self) -> BTreeMap<&str, usize> {
+        let mut labels = BTreeMap::new();
+        for label in self.list.iter().flat_map(|x| &x.labels) {
+            *labels.entry(&label[..]).or_insert(0) += 1;
+        }
+        labels
+    }
+
+    pub fn remove(&mut self, ids: &[usize]) -> Removal {
+        let mut removal = Removal::default();
+        let present = self.list.iter().map(|x| x.id).collect::<HashSet<_>>();
+        let mut seen = HashSet::new();
+        for &id in ids.iter().filter(|&&x| seen.insert(x)) {
+            if present.contains(&id) {
+                removal.removed.push(id);
+            } else {
+                removal.not_found.push(id);
+            }
+        }
+
+        let removed = removal.removed.iter().copied().collect::<HashSet<_>>();
+        self.list.retain(|x| !removed.contains(&x.id));
+        let now = Utc::now();
+        for todo in self.list.iter_mut() {
+            let before = todo.blocked_by.len();
+            todo.blocked_by.retain(|x| !removed.contains(x));
+            if todo.blocked_by.len() < before {
+                todo.updated_at = Some(now);
+                removal.unlinked.push(todo.id);
+            }
+        }
+
+        removal
+    }
+
+    /// Marks `id` as blocked by each of `blockers` and returns how many were new.
+    /// Nothing changes if a link would make a todo wait on itself, directly or through
+    /// other todos.
+    pub fn link(&mut self, id: usize, blockers: &[usize]) -> Result<usize, Error> {
+        if let Some(&x) = [id]
+            .iter()
+            .chain(blockers)
+            .find(|&&x| self.get(x).is_none())
+        {
+            return Err(Error::not_found(x));
+        }
+        for &blocker in blockers {
+            if let Some(path) = Self::blocking_path(|x| self.get(x), blocker, id) {
+                let cycle = [id]
+                    .iter()
+                    .chain(&path)
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>();
+                return Err(Error::Conflict(format!(
+                    "link would create a cycle; {}",
+                    cycle.join(" -> ")
+                )));
+            }
+        }
+
+        let todo = self.list.iter_mut().find(|x| x.id == id).unwrap();
+        let count = blockers
+            .iter()
+            .filter(|&&x| todo.blocked_by.insert(x))
+            .count();
+        if count > 0 {
+            todo.updated_at = Some(Utc::now());
+        }
+
+        Ok(count)
+    }
+
+    /// Removes each of `blockers` from what blocks `id` and returns how many were there.
+    pub fn unlink(&mut self, id: usize, blockers: &[usize]) -> Result<usize, Error> {
+        let todo = match self.list.iter_mut().find(|x| x.id == id) {
+            Some(x) => x,
+            None => return Err(Error::not_found(id)),
+        };
+
+        let count = blockers
+            .iter()
+            .filter(|&&x| todo.blocked_by.remove(&x))
+            .count();
+        if count > 0 {
+            todo.updated_at = Some(Utc::now());
+        }
+
+        Ok(count)
+    }
+
+    /// The chain of `blocked_by` links leading from `from` to `to`, both included,
+    /// if `from` waits on `to` directly or indirectly. `get` looks a todo up by id.
+    fn blocking_path<'a>(
+        get: impl Fn(usize) -> Option<&'a Todo>,
+        from: usize,
+        to: usize,
+    ) -> Option<Vec<usize>> {
+        let mut stack = vec![vec![from]];
+        let mut seen = BTreeSet::new();
+        while let Some(path) = stack.pop() {
+            let id = *path.last().unwrap();
+            if id == to {
+                return Some(path);
+            }
+            if !seen.insert(id) {
+                continue;
+            }
+            // Reversed so the stack tries lower ids first.
+            for &next in get(id).iter().flat_map(|x| x.blocked_by.iter().rev()) {
+                stack.push(path.iter().copied().chain([next]).collect());
+            }
+        }
+
+        None
+    }
+
+    /// Every todo ordered so that each comes after the todos blocking it, lower ids first
+    /// where the order is free. Todos caught in a cycle, which [`TodoList::link`] prevents
+    /// but a hand-edited file may hold, come last in id order.
+    pub fn topological_order(&self) -> Vec<&Todo> {
+        let mut waiting = self
+            .list
+            .iter()
+            .map(|x| {
+                let count = x.blocked_by.iter().filter(|&&b| self.get(b).is_some());
+                (x.id, count.count())
+            })
+            .collect::<BTreeMap<_, _>>();
+        let mut available = waiting
+            .iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(&id, _)| id)
+            .collect::<BTreeSet<_>>();
+
+        let mut order = Vec::new();
+        while let Some(id) = available.pop_first() {
+            waiting.remove(&id);
+            order.push(self.get(id).unwrap());
+            for todo in self.list.iter().filter(|x| x.blocked_by.contains(&id)) {
+                if let Some(count) = waiting.get_mut(&todo.id) {
+                    *count -= 1;
+                    if *count == 0 {
+                        available.insert(todo.id);
+                    }
+                }
+            }
+        }
+        order.extend(waiting.keys().filter_map(|&id| self.get(id)));
+
+        order
+    }
+
+    /// `ToDo` todos whose blockers are all done, in [`TodoList::topological_order`].
+    pub fn ready(&self) -> Vec<&Todo> {
+        self.topological_order()
+            .into_iter()
+            .filter(|x| x.status == Status::ToDo)
+            .filter(|x| {
+                x.blocked_by
+                    .iter()
+                    .filter_map(|&b| self.get(b))
+                    .all(|b| b.status == Status::Done)
+            })
+            .collect()
+    }
+
+    /// Puts `todo` in place under `id`, or removes the todo with `id` if `todo` is `None`.
+    /// Used to replay journaled changes; a todo put back goes before the first higher id.
+    pub fn restore(&mut self, id: usize, todo: Option<Todo>) {
+        match (todo, self.list.iter().position(|x| x.id == id)) {
+            (Some(todo), Some(index)) => self.list[index] = todo,
+            (Some(todo), None) => {
+                let index = self
+                    .list
+                    .iter()
+                    .position(|x| x.id > id)
+                    .unwrap_or(self.list.len());
+                self.list.insert(index, todo);
+                self.next_id = self.next_id.max(id + 1);
+            }
+            (None, Some(index)) => {
+                self.list.remove(index);
+            }
+            (None, None) => (),
+        }
+    }
+
+    /// Like [`TodoList::remove`], but a todo with children is handled by `children`.
+    /// With [`ChildPolicy::Refuse`] nothing is removed if any removed todo would leave
+    /// children behind.
+    pub fn remove_with(&mut self, ids: &[usize], children: ChildPolicy) -> Result<Removal, Error> {
+        let mut ids = ids.to_vec();
+        let mut children_of = HashMap::<usize, Vec<usize>>::new();
+        for todo in &self.list {
+            if let Some(parent) = todo.parent {
+                children_of.entry(parent).or_default().push(todo.id);
+            }
+        }
+        match children {
+            ChildPolicy::Refuse => {
+                let removed = ids.iter().copied().collect::<HashSet<_>>();
+                for &id in &ids {
+                    let kept = children_of
+                        .get(&id)
+                        .into_iter()
+                        .flatten()
+                        .filter(|x| !removed.contains(x))
+                        .map(|x| x.to_string())
+                        .collect::<Vec<_>>();
+                    if !kept.is_empty() {
+                        return Err(Error::Conflict(format!(
+                            "todo {} has children; {}",
+                            id,
+                            kept.join(", ")
+                        )));
+                    }
+                }
+            }
+            ChildPolicy::Cascade => {
+                let mut seen = ids.iter().copied().collect::<HashSet<_>>();
+                for root in ids.clone() {
+                    // Depth first, each todo before its subtasks, as `tree` lists them.
+                    let mut stack = vec![root];
+                    while let Some(id) = stack.pop() {
+                        if id != root {
+                            ids.push(id);
+                        }
+                        let children = children_of.get(&id).into_iter().flatten();
+                        stack.extend(children.rev().filter(|&&x| seen.insert(x)));
+                    }
+                }
+            }
+            ChildPolicy::Reparent => (),
+        }
+
+        let positions = self.positions();
+        let removed = ids.iter().copied().collect::<HashSet<_>>();
+        let parent_of = |id: usize| positions.get(&id).and_then(|&i| self.list[i].parent);
+        let mut parents = BTreeMap::new();
+        for &id in ids.iter().filter(|x| positions.contains_key(x)) {
+            let mut parent = parent_of(id);
+            let mut seen = HashSet::new();
+            while let Some(p) = parent.filter(|x| removed.contains(x) && seen.insert(*x)) {
+                parent = parent_of(p);
+            }
+            parents.insert(id, parent.filter(|x| !removed.contains(x)));
+        }
+
+        let mut removal = self.remove(&ids);
+        for todo in self.list.iter_mut() {
+            if let Some(&parent) = todo.parent.and_then(|x| parents.get(&x)) {
+                todo.parent = parent;
+                removal.reparented.push(todo.id);
+            }
+        }
+
+        Ok(removal)
+    }
+
+    /// Fails unless `parent` exists and is not `id` or one of its descendants.
+    pub fn check_parent(&self, id: Option<usize>, parent: usize) -> Result<(), Error> {
+        let mut ancestor = Some(parent);
+        let mut seen = BTreeSet::new();
+        while let Some(x) = ancestor.filter(|&x| seen.insert(x)) {
+            if Some(x) == id {
+                return Err(Error::Conflict(format!(
+                    "todo {} cannot be a subtask of itself",
+                    x
+                )));
+            }
+            ancestor = match self.get(x) {
+                Some(todo) => todo.parent,
+                None if x == parent => return Err(Error::not_found(parent)),
+                None => None,
+            };
+        }
+
+        Ok(())
+    }
+
+    /// The direct subtasks of `id`, in list order.
+    pub fn children(&self, id: usize) -> impl Iterator<Item = &Todo> {
+        self.list.iter().filter(move |x| x.parent == Some(id))
+    }
+
+    /// How many of the direct subtasks of `id` are done, and how many there are;
+    /// `None` if it has none.
+    pub fn progress(&self, id: usize) -> Option<(usize, usize)> {
+        let (done, total) = self.children(id).fold((0, 0), |(done, total), x| {
+            (done + usize::from(x.status == Status::Done), total + 1)
         });
+        (total > 0).then_some((done, total))
+    }
+
+    /// Todos in depth-first order with their depth, each followed by its subtasks.
+    /// With `root` only that todo and its descendants are given; otherwise every todo
+    /// without a parent in the list is a root.
+    pub fn tree(&self, root: Option<usize>) -> Vec<(usize, &Todo)> {
+        fn visit<'a>(
+            todo_list: &'a TodoList,
+            todo: &'a Todo,
+            depth: usize,
+            seen: &mut BTreeSet<usize>,
+            out: &mut Vec<(usize, &'a Todo)>,
+        ) {
+            if !seen.insert(todo.id) {
+                return;
+            }
+            out.push((depth, todo));
+            for child in todo_list.children(todo.id) {
+                visit(todo_list, child, depth + 1, seen, out);
+            }
+        }
+
+        let roots = match root {
+            Some(id) => self.get(id).into_iter().collect::<Vec<_>>(),
+            None => self
+                .list
+                .iter()
+                .filter(|x| x.parent.is_none_or(|p| self.get(p).is_none()))
+                .collect(),
+        };
+        let mut seen = BTreeSet::new();
+        let mut out = Vec::new();
+        for todo in roots {
+            visit(self, todo, 0, &mut seen, &mut out);
+        }
+
+        out
     }
 
     pub fn list(&self) -> &Vec<Todo> {
         &self.list
     }
 
-    pub fn save(&self, file_path: &str) -> Result<(), Box<dyn Error>> {
-        let file = File::open(file_path)?;
-        let buf = BufWriter::new(file);
-        let json = serde_json::to_string(&self)?;
-        serde_json::to_writer(buf, &json)?;
+    /// Writes the list to a temporary file next to `file_path` and renames it into place,
+    /// so a crash mid-save leaves either the old or the new list on disk, never a partial one.
+    pub fn save(&self, file_path: &str) -> Result<(), Error> {
+        let path = Path::new(file_path);
+        let tmp_path = path.with_file_name(format!(
+            ".{}.{}.tmp",
+            path.file_name()
+                .and_then(|x| x.to_str())
+                .unwrap_or_default(),
+            std::process::id()
+        ));
+
+        let stored = StoredList {
+            version: SCHEMA_VERSION,
+            next_id: self.next_id,
+            todos: &self.list,
+        };
+        let res = Self::write_json(&stored, &tmp_path).and_then(|()| {
+            fs::rename(&tmp_path, path)?;
+            Self::sync_dir(path)
+        });
+        if res.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+
+        res.map_err(|x| x.with_path(path))
+    }
+
+    fn write_json(stored: &StoredList, path: &Path) -> Result<(), Error> {
+        let file = File::create(path)?;
+        let mut buf = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut buf, stored)?;
+        buf.flush()?;
+        buf.get_ref().sync_all()?;
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn sync_dir(path: &Path) -> Result<(), Error> {
+        let dir = match path.parent() {
+            Some(x) if !x.as_os_str().is_empty() => x,
+            _ => Path::new("."),
+        };
+        File::open(dir)?.sync_all()?;
+
+        Ok(())
+    }
 
+    #[cfg(not(unix))]
+    fn sync_dir(_path: &Path) -> Result<(), Error> {
         Ok(())
     }
 }
@@ -96,15 +1109,19 @@ impl TodoList {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{copy_todo_list, labels};
     use rstest::*;
-    use serde_json::Error as JSONError;
-    use std::io::Error as IOError;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_800_000_000 + secs, 0).unwrap()
+    }
 
     fn create_partial_todo0() -> PartialTodo {
         PartialTodo {
             title: String::from("title0"),
             description: String::from("description0"),
-            label: String::from(""),
+            labels: labels(&[]),
+            ..Default::default()
         }
     }
 
@@ -121,21 +1138,24 @@ mod tests {
                                 title: String::from("title0"),
                                 description: String::from("description0"),
                                 status: Status::ToDo,
-                                label: String::from(""),
+                                labels: labels(&[]),
+                                ..Default::default()
                             },
                             Todo {
                                 id: 1,
                                 title: String::from("title1"),
                                 description: String::from("description1"),
                                 status: Status::InProgress,
-                                label: String::from("label1"),
+                                labels: labels(&["label1"]),
+                                ..Default::default()
                             },
                             Todo {
                                 id: 2,
                                 title: String::from("title2"),
                                 description: String::from("description2"),
                                 status: Status::Done,
-                                label: String::from("label2"),
+                                labels: labels(&["label2"]),
+                                ..Default::default()
                             },
                         ]
                         .into_iter()
@@ -154,7 +1174,7 @@ mod tests {
 
             let res = TodoList::load(path);
             match res {
-                Err(err) if err.is::<IOError>() => (),
+                Err(Error::Io { .. }) => (),
                 x => panic!("did not return io::Error; {:?} was returned", x),
             }
         }
@@ -167,21 +1187,138 @@ mod tests {
             assert!(res.is_err(), "not err; res={:?}", res);
 
             let err = res.unwrap_err();
-            assert!(err.is::<JSONError>(), "not expected err; res={:?}", err);
+            assert!(
+                matches!(err, Error::Json { .. }),
+                "not expected err; res={:?}",
+                err
+            );
         }
     }
 
-    mod add_tests {
-        use super::create_partial_todo0;
+    mod save_tests {
         use super::*;
+        use proptest::collection::vec;
+        use proptest::prelude::*;
+        use tempfile::tempdir;
 
-        #[test]
-        fn should_add_new_todo() {
+        fn status_strategy() -> impl Strategy<Value = Status> {
+            prop_oneof![
+                Just(Status::ToDo),
+                Just(Status::InProgress),
+                Just(Status::Done)
+            ]
+        }
+
+        fn priority_strategy() -> impl Strategy<Value = Priority> {
+            prop_oneof![
+                Just(Priority::Low),
+                Just(Priority::Normal),
+                Just(Priority::High),
+                Just(Priority::Urgent)
+            ]
+        }
+
+        fn todo_strategy() -> impl Strategy<Value = Todo> {
+            (
+                any::<usize>(),
+                any::<String>(),
+                any::<String>(),
+                status_strategy(),
+                proptest::collection::btree_set(any::<String>(), 0..3),
+                proptest::option::of(
+                    (0..3_000_000_i32)
+                        .prop_map(|x| NaiveDate::from_num_days_from_ce_opt(x).unwrap()),
+                ),
+                priority_strategy(),
+                proptest::option::of(
+                    (0..4_000_000_000_i64, 0..1_000_000_000_u32)
+                        .prop_map(|(secs, nanos)| DateTime::from_timestamp(secs, nanos).unwrap()),
+                ),
+            )
+                .prop_map(
+                    |(id, title, description, status, labels, due, priority, created_at)| Todo {
+                        id,
+                        title,
+                        description,
+                        status,
+                        labels,
+                        due,
+                        priority,
+                        created_at,
+                        updated_at: created_at,
+                        ..Default::default()
+                    },
+                )
+        }
+
+        proptest! {
+            #[test]
+            fn should_round_trip_with_load(todos in vec(todo_strategy(), 0..20)) {
+                let dir = tempdir().unwrap();
+                let path = dir.path().join("todo-list.json");
+                let path = path.to_str().unwrap();
+                let todo_list = TodoList::new(todos.into_iter());
+
+                todo_list.save(path).unwrap();
+
+                prop_assert_eq!(TodoList::load(path).unwrap(), todo_list);
+            }
+        }
+
+        #[test]
+        fn should_create_file_if_missing() {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("todo-list.json");
+            let path = path.to_str().unwrap();
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            todo_list.add(&create_partial_todo0());
+
+            let res = todo_list.save(path);
+            assert!(res.is_ok(), "not Ok; res={:?}", res);
+
+            assert_eq!(TodoList::load(path).unwrap(), todo_list);
+        }
+
+        #[test]
+        fn should_overwrite_existing_file_without_leaving_temp_files() {
+            let (dir, path) = copy_todo_list();
+            let mut todo_list = TodoList::load(&path).unwrap();
+            todo_list.remove(&[0, 1]);
+
+            todo_list.save(&path).unwrap();
+
+            assert_eq!(TodoList::load(&path).unwrap(), todo_list);
+            assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+        }
+
+        #[test]
+        fn should_return_io_error_if_dir_missing() {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("missing").join("todo-list.json");
+            let todo_list = TodoList::new(Vec::new().into_iter());
+
+            let res = todo_list.save(path.to_str().unwrap());
+            match res {
+                Err(err @ Error::Io { .. }) => {
+                    assert!(err.to_string().starts_with(path.to_str().unwrap()))
+                }
+                x => panic!("did not return io::Error; {:?} was returned", x),
+            }
+        }
+    }
+
+    mod add_tests {
+        use super::create_partial_todo0;
+        use super::*;
+
+        #[test]
+        fn should_add_new_todo() {
             let mut todo_list = TodoList::new(Vec::new().into_iter());
             let todo = create_partial_todo0();
 
-            todo_list.add(&todo);
+            let id = todo_list.add_at(&todo, at(0));
 
+            assert_eq!(id, 0);
             assert_eq!(
                 todo_list.list,
                 vec![Todo {
@@ -189,26 +1326,823 @@ mod tests {
                     title: todo.title,
                     description: todo.description,
                     status: Status::ToDo,
-                    label: todo.label,
+                    labels: todo.labels,
+                    created_at: Some(at(0)),
+                    updated_at: Some(at(0)),
+                    ..Default::default()
                 }]
             );
         }
     }
 
+    mod remove_tests {
+        use super::*;
+
+        fn create_todo_list() -> TodoList {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            for _ in 0..3 {
+                todo_list.add(&create_partial_todo0());
+            }
+            todo_list
+        }
+
+        #[rstest]
+        #[case(vec![1], vec![1], vec![], vec![0, 2])]
+        #[case(vec![0, 2], vec![0, 2], vec![], vec![1])]
+        #[case(vec![1, 5], vec![1], vec![5], vec![0, 2])]
+        #[case(vec![1, 1, 5, 5], vec![1], vec![5], vec![0, 2])]
+        #[case(vec![], vec![], vec![], vec![0, 1, 2])]
+        fn should_remove_todos(
+            #[case] ids: Vec<usize>,
+            #[case] removed: Vec<usize>,
+            #[case] not_found: Vec<usize>,
+            #[case] remaining: Vec<usize>,
+        ) {
+            let mut todo_list = create_todo_list();
+
+            assert_eq!(
+                todo_list.remove(&ids),
+                Removal {
+                    removed,
+                    not_found,
+                    ..Default::default()
+                }
+            );
+            assert_eq!(
+                todo_list.list.iter().map(|x| x.id).collect::<Vec<_>>(),
+                remaining
+            );
+        }
+    }
+
+    mod tree_tests {
+        use super::*;
+
+        /// 0 > (1 > 2, 3 done), 4
+        fn create_todo_list() -> TodoList {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            for parent in [None, Some(0), Some(1), Some(0), None] {
+                todo_list.add(&PartialTodo {
+                    parent,
+                    ..create_partial_todo0()
+                });
+            }
+            let patch = TodoPatch {
+                status: Some(Status::Done),
+                ..Default::default()
+            };
+            todo_list.update(3, &patch).unwrap();
+            todo_list
+        }
+
+        fn parents(todo_list: &TodoList) -> Vec<(usize, Option<usize>)> {
+            todo_list.list.iter().map(|x| (x.id, x.parent)).collect()
+        }
+
+        #[rstest]
+        #[case(None, vec![(0, 0), (1, 1), (2, 2), (1, 3), (0, 4)])]
+        #[case(Some(1), vec![(0, 1), (1, 2)])]
+        #[case(Some(9), vec![])]
+        fn should_walk_tree_depth_first(
+            #[case] root: Option<usize>,
+            #[case] expected: Vec<(usize, usize)>,
+        ) {
+            let todo_list = create_todo_list();
+
+            let tree = todo_list
+                .tree(root)
+                .into_iter()
+                .map(|(depth, x)| (depth, x.id))
+                .collect::<Vec<_>>();
+            assert_eq!(tree, expected);
+        }
+
+        #[rstest]
+        #[case(0, Some((1, 2)))]
+        #[case(1, Some((0, 1)))]
+        #[case(4, None)]
+        fn should_count_done_children(#[case] id: usize, #[case] expected: Option<(usize, usize)>) {
+            assert_eq!(create_todo_list().progress(id), expected);
+        }
+
+        #[rstest]
+        #[case(Some(4), 0, Ok(()))]
+        #[case(None, 9, Err(String::from("todo not found; id=9")))]
+        #[case(Some(0), 2, Err(String::from("todo 0 cannot be a subtask of itself")))]
+        #[case(Some(1), 1, Err(String::from("todo 1 cannot be a subtask of itself")))]
+        fn should_check_parent(
+            #[case] id: Option<usize>,
+            #[case] parent: usize,
+            #[case] expected: Result<(), String>,
+        ) {
+            let todo_list = create_todo_list();
+
+            assert_eq!(
+                todo_list
+                    .check_parent(id, parent)
+                    .map_err(|x| x.to_string()),
+                expected
+            );
+        }
+
+        #[test]
+        fn should_not_update_parent_into_cycle() {
+            let mut todo_list = create_todo_list();
+            let patch = TodoPatch {
+                parent: Some(Some(2)),
+                ..Default::default()
+            };
+
+            let res = todo_list.update(0, &patch);
+            assert!(matches!(res, Err(Error::Conflict(_))), "res={:?}", res);
+            assert_eq!(todo_list.get(0).unwrap().parent, None);
+        }
+
+        #[test]
+        fn should_refuse_to_remove_parent_leaving_children() {
+            let mut todo_list = create_todo_list();
+
+            let res = todo_list.remove_with(&[1], ChildPolicy::Refuse);
+            assert_eq!(res.unwrap_err().to_string(), "todo 1 has children; 2");
+            assert_eq!(todo_list.list.len(), 5);
+
+            let res = todo_list.remove_with(&[1, 2], ChildPolicy::Refuse);
+            assert_eq!(res.unwrap().removed, vec![1, 2]);
+        }
+
+        #[test]
+        fn should_remove_descendants_on_cascade() {
+            let mut todo_list = create_todo_list();
+
+            let removal = todo_list.remove_with(&[0], ChildPolicy::Cascade).unwrap();
+            assert_eq!(removal.removed, vec![0, 1, 2, 3]);
+            assert_eq!(parents(&todo_list), vec![(4, None)]);
+        }
+
+        #[test]
+        fn should_move_children_to_surviving_ancestor_on_reparent() {
+            let mut todo_list = create_todo_list();
+
+            let removal = todo_list
+                .remove_with(&[0, 1], ChildPolicy::Reparent)
+                .unwrap();
+            assert_eq!(removal.reparented, vec![2, 3]);
+            assert_eq!(parents(&todo_list), vec![(2, None), (3, None), (4, None)]);
+
+            let mut todo_list = create_todo_list();
+            todo_list.remove_with(&[1], ChildPolicy::Reparent).unwrap();
+            assert_eq!(todo_list.get(2).unwrap().parent, Some(0));
+        }
+    }
+
+    mod link_tests {
+        use super::*;
+
+        /// 0 done; 1 and 2 blocked by 0; 3 blocked by 1 and 2; 4 in progress
+        fn create_todo_list() -> TodoList {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            for _ in 0..5 {
+                todo_list.add(&create_partial_todo0());
+            }
+            todo_list.link(2, &[0]).unwrap();
+            todo_list.link(1, &[0]).unwrap();
+            todo_list.link(3, &[2, 1]).unwrap();
+            for (id, status) in [(0, Status::Done), (4, Status::InProgress)] {
+                let patch = TodoPatch {
+                    status: Some(status),
+                    ..Default::default()
+                };
+                todo_list.update(id, &patch).unwrap();
+            }
+            todo_list
+        }
+
+        fn ids(todos: Vec<&Todo>) -> Vec<usize> {
+            todos.into_iter().map(|x| x.id).collect()
+        }
+
+        #[rstest]
+        #[case(0, vec![3], Err(String::from("link would create a cycle; 0 -> 3 -> 1 -> 0")))]
+        #[case(4, vec![4], Err(String::from("link would create a cycle; 4 -> 4")))]
+        #[case(4, vec![9], Err(String::from("todo not found; id=9")))]
+        #[case(4, vec![3, 0], Ok(2))]
+        #[case(3, vec![1, 4], Ok(1))]
+        fn should_link_unless_cycle(
+            #[case] id: usize,
+            #[case] blockers: Vec<usize>,
+            #[case] expected: Result<usize, String>,
+        ) {
+            let mut todo_list = create_todo_list();
+            let before = todo_list.list().to_vec();
+
+            let res = todo_list.link(id, &blockers).map_err(|x| x.to_string());
+            assert_eq!(res, expected);
+            if res.is_err() {
+                assert_eq!(todo_list.list(), &before);
+            }
+        }
+
+        #[test]
+        fn should_unlink_blockers() {
+            let mut todo_list = create_todo_list();
+
+            assert_eq!(todo_list.unlink(3, &[1, 4]).unwrap(), 1);
+            assert_eq!(todo_list.get(3).unwrap().blocked_by, BTreeSet::from([2]));
+            assert!(todo_list.unlink(9, &[1]).is_err());
+        }
+
+        #[test]
+        fn should_order_blockers_first() {
+            let mut todo_list = create_todo_list();
+            assert_eq!(ids(todo_list.topological_order()), vec![0, 1, 2, 3, 4]);
+
+            todo_list.link(0, &[4]).unwrap();
+            assert_eq!(ids(todo_list.topological_order()), vec![4, 0, 1, 2, 3]);
+        }
+
+        #[test]
+        fn should_list_todos_whose_blockers_are_done() {
+            let mut todo_list = create_todo_list();
+            assert_eq!(ids(todo_list.ready()), vec![1, 2]);
+
+            let patch = TodoPatch {
+                status: Some(Status::Done),
+                ..Default::default()
+            };
+            todo_list.update(1, &patch).unwrap();
+            todo_list.update(2, &patch).unwrap();
+            assert_eq!(ids(todo_list.ready()), vec![3]);
+        }
+
+        #[test]
+        fn should_remove_dangling_blockers() {
+            let mut todo_list = create_todo_list();
+
+            let removal = todo_list.remove(&[1]);
+            assert_eq!(removal.unlinked, vec![3]);
+            assert_eq!(todo_list.get(3).unwrap().blocked_by, BTreeSet::from([2]));
+        }
+    }
+
+    mod recurrence_tests {
+        use super::*;
+
+        fn date(s: &str) -> Option<NaiveDate> {
+            NaiveDate::parse_from_str(s, crate::date::DATE_FORMAT).ok()
+        }
+
+        fn status(status: Status) -> TodoPatch {
+            TodoPatch {
+                status: Some(status),
+                ..Default::default()
+            }
+        }
+
+        /// A weekly todo due on 2027-01-20, five days after `at(0)`.
+        fn create_todo_list() -> TodoList {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            todo_list.add_at(
+                &PartialTodo {
+                    labels: labels(&["chore"]),
+                    due: date("2027-01-20"),
+                    priority: Priority::High,
+                    recurrence: "weekly".parse().ok(),
+                    ..create_partial_todo0()
+                },
+                at(0),
+            );
+            todo_list
+        }
+
+        #[test]
+        fn should_add_next_occurrence_when_done() {
+            let mut todo_list = create_todo_list();
+
+            let todo = todo_list
+                .update_at(0, &status(Status::Done), at(60))
+                .unwrap();
+            assert_eq!(todo.next, Some(1));
+
+            let next = todo_list.get(1).unwrap();
+            assert_eq!(next.status, Status::ToDo);
+            assert_eq!(next.due, date("2027-01-27"));
+            assert_eq!(next.series, Some(0));
+            assert_eq!(
+                (&next.title, &next.labels, next.priority, next.recurrence),
+                (
+                    &String::from("title0"),
+                    &labels(&["chore"]),
+                    Priority::High,
+                    "weekly".parse().ok()
+                )
+            );
+
+            todo_list
+                .update_at(1, &status(Status::Done), at(120))
+                .unwrap();
+            let next = todo_list.get(2).unwrap();
+            assert_eq!(next.due, date("2027-02-03"));
+            assert_eq!(next.series, Some(0));
+        }
+
+        #[test]
+        fn should_not_add_another_occurrence_when_done_again() {
+            let mut todo_list = create_todo_list();
+
+            todo_list
+                .update_at(0, &status(Status::Done), at(60))
+                .unwrap();
+            todo_list
+                .update_at(0, &status(Status::ToDo), at(120))
+                .unwrap();
+            todo_list
+                .update_at(0, &status(Status::Done), at(180))
+                .unwrap();
+            assert_eq!(todo_list.list.len(), 2);
+        }
+
+        #[test]
+        fn should_not_add_occurrence_unless_recurring_todo_is_done() {
+            let mut todo_list = create_todo_list();
+
+            todo_list
+                .update_at(0, &status(Status::InProgress), at(60))
+                .unwrap();
+            let patch = TodoPatch {
+                recurrence: Some(None),
+                status: Some(Status::Done),
+                ..Default::default()
+            };
+            todo_list.update_at(0, &patch, at(120)).unwrap();
+            assert_eq!(todo_list.list.len(), 1);
+        }
+    }
+
+    mod update_tests {
+        use super::*;
+
+        #[test]
+        fn should_update_only_given_fields() {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            let id = todo_list.add_at(&create_partial_todo0(), at(0));
+
+            let res = todo_list.update_at(
+                id,
+                &TodoPatch {
+                    title: Some(String::from("title1")),
+                    status: Some(Status::InProgress),
+                    ..Default::default()
+                },
+                at(60),
+            );
+
+            assert_eq!(
+                res.unwrap(),
+                &Todo {
+                    id,
+                    title: String::from("title1"),
+                    description: String::from("description0"),
+                    status: Status::InProgress,
+                    labels: labels(&[]),
+                    created_at: Some(at(0)),
+                    updated_at: Some(at(60)),
+                    history: vec![StatusChange {
+                        from: Status::ToDo,
+                        to: Status::InProgress,
+                        at: at(60),
+                    }],
+                    ..Default::default()
+                }
+            );
+        }
+
+        #[test]
+        fn should_record_status_changes_and_cycle_time() {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            let id = todo_list.add_at(&create_partial_todo0(), at(0));
+            let status = |x| TodoPatch {
+                status: Some(x),
+                ..Default::default()
+            };
+
+            todo_list
+                .update_at(id, &status(Status::InProgress), at(60))
+                .unwrap();
+            todo_list
+                .update_at(id, &status(Status::InProgress), at(90))
+                .unwrap();
+            todo_list
+                .update_at(id, &status(Status::Done), at(3600))
+                .unwrap();
+
+            let todo = todo_list.get(id).unwrap();
+            assert_eq!(
+                todo.history
+                    .iter()
+                    .map(|x| (x.from, x.to, x.at))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (Status::ToDo, Status::InProgress, at(60)),
+                    (Status::InProgress, Status::Done, at(3600)),
+                ]
+            );
+            assert_eq!(todo.completed_at, Some(at(3600)));
+            assert_eq!(todo.cycle_time(), Some(TimeDelta::seconds(3540)));
+
+            let todo = todo_list
+                .update_at(id, &status(Status::ToDo), at(7200))
+                .unwrap();
+            assert_eq!(todo.completed_at, None);
+            assert_eq!(todo.cycle_time(), None);
+            assert_eq!(todo.history.len(), 3);
+        }
+
+        #[rstest]
+        #[case(1, TodoPatch::default(), "todo not found; id=1")]
+        #[case(
+            0,
+            TodoPatch { title: Some(String::from("")), ..Default::default() },
+            "title must not be empty"
+        )]
+        fn should_return_error_if_patch_is_invalid(
+            #[case] id: usize,
+            #[case] patch: TodoPatch,
+            #[case] expected: &str,
+        ) {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            todo_list.add(&create_partial_todo0());
+
+            let res = todo_list.update(id, &patch).map(|_| ());
+            assert_eq!(res.unwrap_err().to_string(), expected);
+            assert_eq!(todo_list.list[0].title, "title0");
+        }
+    }
+
+    mod import_tests {
+        use super::*;
+
+        fn create_todo(id: usize, title: &str) -> Todo {
+            Todo {
+                id,
+                title: String::from(title),
+                description: String::from(""),
+                status: Status::Done,
+                labels: labels(&[]),
+                ..Default::default()
+            }
+        }
+
+        fn titles(todo_list: &TodoList) -> Vec<(usize, &str)> {
+            todo_list
+                .list
+                .iter()
+                .map(|x| (x.id, &x.title[..]))
+                .collect()
+        }
+
+        #[rstest]
+        #[case(
+            Conflict::Renumber,
+            ImportReport { added: vec![5], renumbered: vec![(0, 6)], ..Default::default() },
+            vec![(0, "title0"), (1, "title0"), (5, "new5"), (6, "new0")]
+        )]
+        #[case(
+            Conflict::Skip,
+            ImportReport { added: vec![5], skipped: vec![0], ..Default::default() },
+            vec![(0, "title0"), (1, "title0"), (5, "new5")]
+        )]
+        #[case(
+            Conflict::Overwrite,
+            ImportReport { added: vec![5], overwritten: vec![0], ..Default::default() },
+            vec![(0, "new0"), (1, "title0"), (5, "new5")]
+        )]
+        fn should_import_todos(
+            #[case] on_conflict: Conflict,
+            #[case] expected_report: ImportReport,
+            #[case] expected: Vec<(usize, &str)>,
+        ) {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            todo_list.add(&create_partial_todo0());
+            todo_list.add(&create_partial_todo0());
+
+            let report = todo_list.import(
+                vec![create_todo(5, "new5"), create_todo(0, "new0")],
+                on_conflict,
+            );
+
+            assert_eq!(report, expected_report);
+            assert_eq!(titles(&todo_list), expected);
+        }
+
+        #[test]
+        fn should_follow_links_to_renumbered_ids() {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            for _ in 0..3 {
+                todo_list.add(&create_partial_todo0());
+            }
+            let mut todos = vec![
+                create_todo(0, "parent"),
+                create_todo(1, "child"),
+                create_todo(2, "orphan"),
+            ];
+            todos[1].parent = Some(0);
+            todos[1].blocked_by = BTreeSet::from([2]);
+            todos[2].parent = Some(7);
+            todos[2].blocked_by = BTreeSet::from([0, 8]);
+
+            let report = todo_list.import(todos, Conflict::Renumber);
+
+            assert_eq!(report.renumbered, vec![(0, 3), (1, 4), (2, 5)]);
+            assert_eq!(report.detached, vec![5]);
+            assert_eq!(todo_list.get(4).unwrap().parent, Some(3));
+            assert_eq!(todo_list.get(4).unwrap().blocked_by, BTreeSet::from([5]));
+            assert_eq!(todo_list.get(5).unwrap().parent, None);
+            assert_eq!(todo_list.get(5).unwrap().blocked_by, BTreeSet::from([3]));
+        }
+
+        #[test]
+        fn should_follow_occurrences_to_renumbered_ids() {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            for _ in 0..3 {
+                todo_list.add(&create_partial_todo0());
+            }
+            let mut todos = vec![create_todo(0, "first"), create_todo(1, "second")];
+            todos[0].next = Some(1);
+            todos[1].series = Some(0);
+            todos[1].next = Some(9);
+
+            let report = todo_list.import(todos, Conflict::Renumber);
+
+            assert_eq!(report.detached, vec![4]);
+            assert_eq!(todo_list.get(3).unwrap().next, Some(4));
+            assert_eq!(todo_list.get(4).unwrap().series, Some(3));
+            assert_eq!(todo_list.get(4).unwrap().next, None);
+        }
+
+        #[test]
+        fn should_detach_parents_that_would_make_cycle_on_overwrite() {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            todo_list.add(&create_partial_todo0());
+            todo_list.add(&PartialTodo {
+                parent: Some(0),
+                ..create_partial_todo0()
+            });
+            let mut todo = create_todo(0, "new0");
+            todo.parent = Some(1);
+
+            let report = todo_list.import(vec![todo], Conflict::Overwrite);
+
+            assert_eq!(report.detached, vec![0]);
+            assert_eq!(todo_list.get(0).unwrap().parent, None);
+            assert_eq!(todo_list.get(1).unwrap().parent, Some(0));
+        }
+
+        #[test]
+        fn should_drop_blockers_that_would_make_cycle_on_overwrite() {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            todo_list.add(&create_partial_todo0());
+            todo_list.add(&create_partial_todo0());
+            todo_list.link(1, &[0]).unwrap();
+            let mut todo = create_todo(0, "new0");
+            todo.blocked_by = BTreeSet::from([1]);
+
+            let report = todo_list.import(vec![todo], Conflict::Overwrite);
+
+            assert_eq!(report.detached, vec![0]);
+            assert!(todo_list.get(0).unwrap().blocked_by.is_empty());
+            assert_eq!(todo_list.get(1).unwrap().blocked_by, BTreeSet::from([0]));
+        }
+
+        #[test]
+        fn should_append_todos_with_new_ids() {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            todo_list.add(&create_partial_todo0());
+
+            let ids = todo_list.append(vec![create_todo(0, "new0"), create_todo(0, "new1")]);
+
+            assert_eq!(ids, vec![1, 2]);
+            assert_eq!(
+                titles(&todo_list),
+                vec![(0, "title0"), (1, "new0"), (2, "new1")]
+            );
+        }
+    }
+
+    mod label_tests {
+        use super::*;
+
+        fn create_todo_list() -> TodoList {
+            TodoList::load("test_assets/todo-list.json").unwrap()
+        }
+
+        fn all_labels(todo_list: &TodoList) -> Vec<Vec<&str>> {
+            todo_list
+                .list
+                .iter()
+                .map(|x| x.labels.iter().map(|x| &x[..]).collect())
+                .collect()
+        }
+
+        #[test]
+        fn should_add_label() {
+            let mut todo_list = create_todo_list();
+
+            assert_eq!(todo_list.add_label("label1", &[0, 1]).unwrap(), vec![0]);
+            assert_eq!(
+                all_labels(&todo_list),
+                vec![vec!["label1"], vec!["label1"], vec!["label2"]]
+            );
+            assert!(matches!(
+                todo_list.add_label("label1", &[9]),
+                Err(Error::NotFound(x)) if x == "9"
+            ));
+            assert!(matches!(
+                todo_list.add_label("", &[0]),
+                Err(Error::Invalid(_))
+            ));
+        }
+
+        #[rstest]
+        #[case(vec![], Ok(vec![0, 1]), vec![vec![], vec![], vec!["label2"]])]
+        #[case(vec![1], Ok(vec![1]), vec![vec!["label1"], vec![], vec!["label2"]])]
+        #[case(vec![2], Ok(vec![]), vec![vec!["label1"], vec!["label1"], vec!["label2"]])]
+        #[case(
+            vec![9],
+            Err(String::from("todo not found; id=9")),
+            vec![vec!["label1"], vec!["label1"], vec!["label2"]]
+        )]
+        fn should_remove_label(
+            #[case] ids: Vec<usize>,
+            #[case] expected: Result<Vec<usize>, String>,
+            #[case] expected_labels: Vec<Vec<&str>>,
+        ) {
+            let mut todo_list = create_todo_list();
+            todo_list.add_label("label1", &[0]).unwrap();
+
+            let res = todo_list.remove_label("label1", &ids);
+            assert_eq!(res.map_err(|x| x.to_string()), expected);
+            assert_eq!(all_labels(&todo_list), expected_labels);
+        }
+
+        #[test]
+        fn should_rename_label_everywhere_merging_duplicates() {
+            let mut todo_list = create_todo_list();
+            todo_list.add_label("label1", &[2]).unwrap();
+
+            assert_eq!(
+                todo_list.rename_label("label1", "label2").unwrap(),
+                vec![1, 2]
+            );
+            assert_eq!(
+                all_labels(&todo_list),
+                vec![vec![], vec!["label2"], vec!["label2"]]
+            );
+            assert!(todo_list
+                .rename_label("missing", "label3")
+                .unwrap()
+                .is_empty());
+        }
+
+        #[test]
+        fn should_count_labels() {
+            let mut todo_list = create_todo_list();
+            todo_list.add_label("label1", &[0, 2]).unwrap();
+
+            assert_eq!(
+                todo_list.labels(),
+                BTreeMap::from([("label1", 3), ("label2", 1)])
+            );
+        }
+
+        #[rstest]
+        #[case(r#""label": """#, vec![])]
+        #[case(r#""label": "label1""#, vec!["label1"])]
+        #[case(r#""labels": ["b", "a", "b"]"#, vec!["a", "b"])]
+        #[case(r#""labels": []"#, vec![])]
+        fn should_deserialize_single_and_multiple_labels(
+            #[case] labels_json: &str,
+            #[case] expected: Vec<&str>,
+        ) {
+            let json = format!(
+                r#"{{"id": 0, "title": "t", "description": "", "status": "ToDo", {}}}"#,
+                labels_json
+            );
+
+            let todo: Todo = serde_json::from_str(&json).unwrap();
+            assert_eq!(todo.labels, labels(&expected));
+        }
+
+        #[test]
+        fn should_deserialize_todo_without_labels() {
+            let todo: Todo = serde_json::from_str(
+                r#"{"id": 0, "title": "t", "description": "", "status": "ToDo"}"#,
+            )
+            .unwrap();
+
+            assert!(todo.labels.is_empty());
+        }
+    }
+
+    mod status_tests {
+        use super::*;
+
+        #[rstest]
+        #[case("low", Ok(Priority::Low))]
+        #[case("Normal", Ok(Priority::Normal))]
+        #[case("HIGH", Ok(Priority::High))]
+        #[case("urgent", Ok(Priority::Urgent))]
+        #[case("asap", Err(String::from("invalid priority; asap")))]
+        fn should_parse_priority(#[case] s: &str, #[case] expected: Result<Priority, String>) {
+            assert_eq!(s.parse::<Priority>(), expected);
+        }
+
+        #[test]
+        fn should_default_due_and_priority_for_old_files() {
+            let todo: Todo = serde_json::from_str(
+                r#"{"id": 0, "title": "t", "description": "", "status": "ToDo", "label": ""}"#,
+            )
+            .unwrap();
+
+            assert_eq!(todo.due, None);
+            assert_eq!(todo.priority, Priority::Normal);
+        }
+
+        #[rstest]
+        #[case("ToDo", Ok(Status::ToDo))]
+        #[case("inprogress", Ok(Status::InProgress))]
+        #[case("DONE", Ok(Status::Done))]
+        #[case("Doing", Err(String::from("invalid status; Doing")))]
+        fn should_parse_status(#[case] s: &str, #[case] expected: Result<Status, String>) {
+            assert_eq!(s.parse::<Status>(), expected);
+        }
+    }
+
     mod get_id_tests {
-        use super::create_partial_todo0;
-        use crate::TodoList;
+        use super::*;
+        use tempfile::tempdir;
 
         #[test]
         fn should_return_expected_id() {
             let mut todo_list = TodoList::new(Vec::new().into_iter());
             let todo = create_partial_todo0();
 
-            assert_eq!(todo_list.get_next_id(), 0);
+            assert_eq!(todo_list.allocate_id(), 0);
 
             todo_list.add(&todo);
 
-            assert_eq!(todo_list.get_next_id(), 1);
+            assert_eq!(todo_list.allocate_id(), 2);
+        }
+
+        #[test]
+        fn should_not_reuse_removed_ids_across_saves() {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("todo-list.json");
+            let path = path.to_str().unwrap();
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            todo_list.add(&create_partial_todo0());
+            todo_list.add(&create_partial_todo0());
+            todo_list.remove(&[0, 1]);
+            todo_list.save(path).unwrap();
+
+            let mut todo_list = TodoList::load(path).unwrap();
+
+            assert_eq!(todo_list.add(&create_partial_todo0()), 2);
+        }
+
+        #[test]
+        fn should_continue_after_highest_id_of_unversioned_file() {
+            let mut todo_list = TodoList::load("test_assets/todo-list.json").unwrap();
+            todo_list.remove(&[2]);
+
+            assert_eq!(todo_list.add(&create_partial_todo0()), 3);
+        }
+
+        #[test]
+        fn should_return_error_if_file_version_is_newer() {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("todo-list.json");
+            fs::write(&path, r#"{"version": 99, "next_id": 0, "todos": []}"#).unwrap();
+
+            let res = TodoList::load(path.to_str().unwrap());
+
+            assert!(res.is_err(), "not err; res={:?}", res);
+            assert_eq!(res.unwrap_err().to_string(), "unsupported file version; 99");
+        }
+
+        #[test]
+        fn should_resolve_numeric_and_hash_ids() {
+            let mut todo_list = TodoList::new(Vec::new().into_iter());
+            for secs in 0..3 {
+                todo_list.add_at(&create_partial_todo0(), at(secs));
+            }
+            let hash_id = todo_list.get(1).unwrap().hash_id();
+
+            assert_eq!(hash_id.len(), 7);
+            assert_eq!(todo_list.resolve("1").unwrap(), 1);
+            assert_eq!(todo_list.resolve(&hash_id).unwrap(), 1);
+            assert_eq!(todo_list.resolve(&hash_id[..4].to_uppercase()).unwrap(), 1);
+            assert!(matches!(todo_list.resolve("zzz"), Err(Error::NotFound(x)) if x == "zzz"));
+            assert!(matches!(todo_list.resolve(""), Err(Error::NotFound(x)) if x.is_empty()));
         }
     }
 }